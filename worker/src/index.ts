@@ -21,7 +21,10 @@ const MAX_PROMPT_CHARS = 2048;
 function trimToMaxPrompt(s: string, maxChars = MAX_PROMPT_CHARS): string {
   const t = (s ?? "").trim();
   if (t.length <= maxChars) return t;
-  return t.slice(t.length - maxChars);
+  const out = t.slice(t.length - maxChars);
+  // .length counts UTF-16 units: don't start on the second half of a surrogate pair.
+  const c = out.charCodeAt(0);
+  return c >= 0xdc00 && c <= 0xdfff ? out.slice(1) : out;
 }
 
 function parseAllowedOrigins(env: Env): Set<string> {
@@ -56,7 +59,7 @@ function corsHeaders(request: Request, env: Env): Record<string, string> {
     "Access-Control-Allow-Headers": "Content-Type, Authorization",
     "Access-Control-Max-Age": "86400",
     // Lets your browser JS read these response headers (optional but handy)
-    "Access-Control-Expose-Headers": "X-Model, X-Steps, X-Style, X-Num-Steps, X-Guidance, X-Prompt-Chars",
+    "Access-Control-Expose-Headers": "X-Model, X-Steps, X-Style, X-Num-Steps, X-Guidance, X-Prompt-Chars, Retry-After",
   };
 
   // Only add Allow-Origin when this is an allowed browser origin.
@@ -87,6 +90,8 @@ function respond(
  * - Adds crop-safe 16:9 composition guidance for your eBook layouts.
  *
  * NOTE: Keep this under MAX_PROMPT_CHARS after all lines are combined.
+ * The Yew app mirrors these lines in yew/src/worker_prompt.rs to preview the
+ * final prompt; update both together.
  */
 function buildStyledPrompt(userPrompt: string, style: string) {
   // Hard anti-text instruction (FLUX needs this in the prompt)
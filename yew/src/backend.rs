@@ -0,0 +1,877 @@
+use gloo_net::http::{Request, RequestBuilder, Response};
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::JsCast;
+use web_sys::{AbortController, AbortSignal};
+
+use crate::batch::sleep_ms;
+use crate::errors::GenError;
+use crate::settings::{GenSettings, ImageModel, TIMEOUT_SECS_MAX, TIMEOUT_SECS_MIN};
+use crate::slots::pretty_slot_name;
+use crate::worker_prompt::{js_length, simulate_worker_prompt};
+use crate::{canvas_2d, crop_16x9, ImageMeta};
+
+// ----------------------------
+// Image backends
+// ----------------------------
+// Everything that talks to an image server sits behind `ImageBackend`. The
+// Cloudflare Worker is the default; Automatic1111 and ComfyUI let a team use
+// a local GPU box, and the placeholder backend draws images without any
+// server. Self-hosted backends get the same prompt and negatives the Worker
+// would build, so images look alike whichever backend made them.
+
+const RETRY_AFTER_MAX_MS: u32 = 120_000;
+const COMFY_POLL_MS: u32 = 1_000;
+const COMFY_MAX_POLLS: u32 = 600; // ten minutes per image, queue wait included
+const COMFY_CLEANUP_SECS: u32 = 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    #[default]
+    Cloudflare,
+    Automatic1111,
+    ComfyUi,
+    Placeholder,
+}
+
+impl BackendKind {
+    pub const ALL: [BackendKind; 4] = [
+        BackendKind::Cloudflare,
+        BackendKind::Automatic1111,
+        BackendKind::ComfyUi,
+        BackendKind::Placeholder,
+    ];
+
+    /// Stable id for LocalStorage.
+    pub fn id(self) -> &'static str {
+        match self {
+            BackendKind::Cloudflare => "cloudflare",
+            BackendKind::Automatic1111 => "a1111",
+            BackendKind::ComfyUi => "comfyui",
+            BackendKind::Placeholder => "placeholder",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BackendKind::Cloudflare => "Cloudflare Worker",
+            BackendKind::Automatic1111 => "Automatic1111 (self-hosted)",
+            BackendKind::ComfyUi => "ComfyUI (self-hosted)",
+            BackendKind::Placeholder => "Offline placeholders",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<BackendKind> {
+        BackendKind::ALL.into_iter().find(|k| k.id() == id)
+    }
+
+    pub fn default_url(self) -> &'static str {
+        match self {
+            BackendKind::Cloudflare => {
+                "https://ebook-image-forge.mikegyver.workers.dev/api/generate"
+            }
+            BackendKind::Automatic1111 => "http://127.0.0.1:7860",
+            BackendKind::ComfyUi => "http://127.0.0.1:8188",
+            BackendKind::Placeholder => "",
+        }
+    }
+}
+
+/// What a backend can do with our settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capabilities {
+    pub models: &'static [ImageModel], // parameter sets it understands (SDXL = size, steps, guidance, negative)
+    pub setup: &'static str,           // what the server needs before the browser can reach it
+    pub original_ext: &'static str,    // file type it delivers: "jpg" | "png"
+    pub retry_timeouts: bool,          // false when a timed-out render keeps running on the server
+}
+
+/// One image to make.
+pub struct GenJob<'a> {
+    pub key: &'a str, // slot key
+    pub prompt: &'a str,
+    pub worker_style: &'a str, // "animated3d" | "storybook"
+    pub seed: Option<u32>,
+    pub settings: &'a GenSettings,
+}
+
+/// Raw image bytes plus whatever the backend told us about them.
+pub struct GeneratedImage {
+    pub bytes: Vec<u8>,
+    pub mime: &'static str,
+    pub meta: ImageMeta,
+}
+
+#[allow(async_fn_in_trait)] // only used with concrete types, never boxed
+pub trait ImageBackend {
+    fn capabilities(&self) -> Capabilities;
+
+    async fn generate(
+        &self,
+        job: &GenJob<'_>,
+        signal: Option<&AbortSignal>,
+    ) -> Result<GeneratedImage, GenError>;
+
+    /// Short description of the server (version, loaded model) if it answers.
+    async fn health(&self, signal: Option<&AbortSignal>) -> Result<String, GenError>;
+}
+
+/// The configured backend.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Backend {
+    Cloudflare(CloudflareWorker),
+    Automatic1111(Automatic1111),
+    ComfyUi(ComfyUi),
+    Placeholder(Placeholder),
+}
+
+impl Backend {
+    /// `url` is the Worker's /api/generate URL or the server's base URL.
+    pub fn new(kind: BackendKind, url: &str, token: &str) -> Backend {
+        let url = url.trim().to_string();
+        let token = token.trim().to_string();
+        match kind {
+            BackendKind::Cloudflare => Backend::Cloudflare(CloudflareWorker { url, token }),
+            BackendKind::Automatic1111 => Backend::Automatic1111(Automatic1111 {
+                base: base_url(&url),
+                token,
+            }),
+            BackendKind::ComfyUi => Backend::ComfyUi(ComfyUi {
+                base: base_url(&url),
+            }),
+            BackendKind::Placeholder => Backend::Placeholder(Placeholder),
+        }
+    }
+}
+
+impl ImageBackend for Backend {
+    fn capabilities(&self) -> Capabilities {
+        match self {
+            Backend::Cloudflare(b) => b.capabilities(),
+            Backend::Automatic1111(b) => b.capabilities(),
+            Backend::ComfyUi(b) => b.capabilities(),
+            Backend::Placeholder(b) => b.capabilities(),
+        }
+    }
+
+    async fn generate(
+        &self,
+        job: &GenJob<'_>,
+        signal: Option<&AbortSignal>,
+    ) -> Result<GeneratedImage, GenError> {
+        match self {
+            Backend::Cloudflare(b) => b.generate(job, signal).await,
+            Backend::Automatic1111(b) => b.generate(job, signal).await,
+            Backend::ComfyUi(b) => b.generate(job, signal).await,
+            Backend::Placeholder(b) => b.generate(job, signal).await,
+        }
+    }
+
+    async fn health(&self, signal: Option<&AbortSignal>) -> Result<String, GenError> {
+        match self {
+            Backend::Cloudflare(b) => b.health(signal).await,
+            Backend::Automatic1111(b) => b.health(signal).await,
+            Backend::ComfyUi(b) => b.health(signal).await,
+            Backend::Placeholder(b) => b.health(signal).await,
+        }
+    }
+}
+
+// ----------------------------
+// HTTP helpers
+// ----------------------------
+fn base_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+/// Retry-After is either delay-seconds or an HTTP date.
+fn parse_retry_after(v: &str) -> Option<u32> {
+    let v = v.trim();
+    let ms = match v.parse::<f64>() {
+        Ok(secs) => secs * 1000.0,
+        Err(_) => js_sys::Date::parse(v) - js_sys::Date::now(),
+    };
+    ms.is_finite()
+        .then(|| ms.clamp(0.0, RETRY_AFTER_MAX_MS as f64) as u32)
+}
+
+/// The request timeout from `st`, within the allowed range.
+fn request_timeout_secs(st: &GenSettings) -> u32 {
+    st.timeout_secs.clamp(TIMEOUT_SECS_MIN, TIMEOUT_SECS_MAX)
+}
+
+/// Abort signal for one HTTP call (send and read). It fires when `secs` run
+/// out, or when `cancel` does (the batch's Cancel). Dropping it clears the
+/// timer and stops listening to `cancel`.
+pub struct RequestTimeout {
+    controller: Option<AbortController>,
+    abort: Option<js_sys::Function>, // controller.abort, bound
+    timer: Option<i32>,
+    cancel: Option<AbortSignal>,
+    secs: u32,
+}
+
+impl RequestTimeout {
+    pub fn start(secs: u32, cancel: Option<&AbortSignal>) -> RequestTimeout {
+        let controller = AbortController::new().ok();
+        let abort = controller.as_ref().and_then(|c| {
+            js_sys::Reflect::get(c, &"abort".into())
+                .ok()?
+                .dyn_into::<js_sys::Function>()
+                .ok()
+                .map(|f| f.bind0(c))
+        });
+        let timer = abort.as_ref().and_then(|abort| {
+            web_sys::window()?
+                .set_timeout_with_callback_and_timeout_and_arguments_0(abort, (secs * 1000) as i32)
+                .ok()
+        });
+        if let (Some(c), Some(cancel), Some(abort)) = (&controller, cancel, &abort) {
+            if cancel.aborted() {
+                c.abort();
+            } else {
+                let _ = cancel.add_event_listener_with_callback("abort", abort);
+            }
+        }
+        RequestTimeout {
+            controller,
+            abort,
+            timer,
+            cancel: cancel.cloned(),
+            secs,
+        }
+    }
+
+    pub fn signal(&self) -> Option<AbortSignal> {
+        self.controller.as_ref().map(|c| c.signal())
+    }
+
+    /// `result`, with the failure of a call the timer aborted reported as a timeout.
+    pub fn check<T>(&self, result: Result<T, GenError>) -> Result<T, GenError> {
+        let fired = self.signal().map(|s| s.aborted()).unwrap_or(false);
+        let canceled = self.cancel.as_ref().map(|c| c.aborted()).unwrap_or(false);
+        match result {
+            Err(_) if fired && !canceled => Err(GenError::Timeout(self.secs)),
+            r => r,
+        }
+    }
+}
+
+impl Drop for RequestTimeout {
+    fn drop(&mut self) {
+        if let (Some(w), Some(timer)) = (web_sys::window(), self.timer) {
+            w.clear_timeout_with_handle(timer);
+        }
+        if let (Some(cancel), Some(abort)) = (&self.cancel, &self.abort) {
+            let _ = cancel.remove_event_listener_with_callback("abort", abort);
+        }
+    }
+}
+
+fn build(r: RequestBuilder) -> Result<Request, GenError> {
+    r.build()
+        .map_err(|e| GenError::Network(format!("could not build request: {e}")))
+}
+
+fn json_body(r: RequestBuilder, body: &impl Serialize) -> Result<Request, GenError> {
+    r.json(body)
+        .map_err(|e| GenError::Network(format!("could not build request: {e}")))
+}
+
+/// Send, and turn non-2xx answers into typed errors.
+async fn send(req: Request) -> Result<Response, GenError> {
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| GenError::Network(e.to_string()))?;
+    if resp.ok() {
+        return Ok(resp);
+    }
+    let retry_after_ms = resp
+        .headers()
+        .get("Retry-After")
+        .and_then(|v| parse_retry_after(&v));
+    // Read text error if present; helps debug without "CORS" confusion
+    let body = resp
+        .text()
+        .await
+        .unwrap_or_else(|_| "Request failed".into());
+    Err(GenError::from_status(resp.status(), body, retry_after_ms))
+}
+
+async fn read_bytes(resp: Response) -> Result<Vec<u8>, GenError> {
+    let bytes = resp
+        .binary()
+        .await
+        .map_err(|e| GenError::Network(format!("response body interrupted: {e}")))?;
+    if bytes.is_empty() {
+        return Err(GenError::EmptyBody);
+    }
+    Ok(bytes)
+}
+
+async fn read_json(resp: Response) -> Result<Value, GenError> {
+    resp.json::<Value>()
+        .await
+        .map_err(|e| GenError::Decode(format!("unexpected response: {e}")))
+}
+
+fn decode_base64(b64: &str) -> Result<Vec<u8>, GenError> {
+    let window = web_sys::window().ok_or_else(|| GenError::Decode("no window".into()))?;
+    let binary = window
+        .atob(b64)
+        .map_err(|_| GenError::Decode("image is not valid base64".into()))?;
+    Ok(binary.chars().map(|c| c as u8).collect())
+}
+
+/// The prompt and negatives the Worker would send, for backends that don't add them.
+fn worker_equivalent(job: &GenJob) -> (String, String) {
+    let w = simulate_worker_prompt(
+        job.prompt,
+        job.worker_style,
+        ImageModel::Sdxl,
+        &job.settings.negative_prompt,
+    );
+    (w.sent, w.negative.unwrap_or_default())
+}
+
+/// Self-hosted samplers need a seed; mirror the Worker's "random when unset".
+fn seed_or_random(seed: Option<u32>) -> u32 {
+    seed.unwrap_or_else(|| (js_sys::Math::random() * u32::MAX as f64) as u32)
+}
+
+// ----------------------------
+// Cloudflare Worker (default)
+// ----------------------------
+#[derive(Clone, Debug, PartialEq)]
+pub struct CloudflareWorker {
+    url: String, // .../api/generate
+    token: String,
+}
+
+#[derive(Serialize)]
+struct GenerateReq<'a> {
+    prompt: &'a str,
+    model: &'a str, // "flux" | "sdxl"
+    style: &'a str, // "animated3d" | "storybook"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    steps: Option<u32>, // flux: max 8
+    seed: Option<u32>,
+
+    // sdxl
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_steps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guidance: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    negative_prompt: Option<&'a str>,
+}
+
+impl<'a> GenerateReq<'a> {
+    /// Request body for one prompt; only the chosen model's fields are sent.
+    fn new(prompt: &'a str, style: &'a str, seed: Option<u32>, settings: &'a GenSettings) -> Self {
+        let sdxl = settings.model == ImageModel::Sdxl;
+        let negative = settings.negative_prompt.trim();
+        GenerateReq {
+            prompt,
+            model: settings.model.id(),
+            style,
+            steps: (!sdxl).then_some(settings.flux_steps),
+            seed,
+            width: sdxl.then_some(settings.width),
+            height: sdxl.then_some(settings.height),
+            num_steps: sdxl.then_some(settings.num_steps),
+            guidance: sdxl.then_some(settings.guidance),
+            negative_prompt: (sdxl && !negative.is_empty()).then_some(negative),
+        }
+    }
+}
+
+/// Metadata the Worker sends back in X-* headers.
+fn worker_meta(headers: &gloo_net::http::Headers) -> ImageMeta {
+    let num = |name: &str| headers.get(name).and_then(|v| v.trim().parse().ok());
+    ImageMeta {
+        model: headers.get("X-Model"),
+        steps: num("X-Steps").or_else(|| num("X-Num-Steps")),
+        guidance: headers
+            .get("X-Guidance")
+            .and_then(|v| v.trim().parse().ok()),
+        style: headers.get("X-Style"),
+        prompt_chars: num("X-Prompt-Chars"),
+        elapsed_ms: None,
+    }
+}
+
+impl ImageBackend for CloudflareWorker {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            models: &ImageModel::ALL,
+            setup: "Add this page's origin to the Worker's ALLOWED_ORIGINS.",
+            original_ext: "jpg",
+            retry_timeouts: true,
+        }
+    }
+
+    async fn generate(
+        &self,
+        job: &GenJob<'_>,
+        signal: Option<&AbortSignal>,
+    ) -> Result<GeneratedImage, GenError> {
+        let req = GenerateReq::new(job.prompt, job.worker_style, job.seed, job.settings);
+        let mut r = Request::post(&self.url).header("Content-Type", "application/json");
+        if !self.token.is_empty() {
+            r = r.header("Authorization", &format!("Bearer {}", self.token));
+        }
+        let call = RequestTimeout::start(request_timeout_secs(job.settings), signal);
+        let r = r.abort_signal(call.signal().as_ref());
+        let resp = call.check(send(json_body(r, &req)?).await)?;
+        let meta = worker_meta(&resp.headers());
+        Ok(GeneratedImage {
+            bytes: call.check(read_bytes(resp).await)?,
+            mime: "image/jpeg",
+            meta,
+        })
+    }
+
+    async fn health(&self, signal: Option<&AbortSignal>) -> Result<String, GenError> {
+        let base = self
+            .url
+            .trim_end_matches('/')
+            .trim_end_matches("/api/generate");
+        let r = Request::get(&format!("{base}/__version")).abort_signal(signal);
+        let resp = send(build(r)?).await?;
+        resp.text()
+            .await
+            .map_err(|e| GenError::Network(format!("response body interrupted: {e}")))
+    }
+}
+
+// ----------------------------
+// Automatic1111 (/sdapi/v1/txt2img)
+// ----------------------------
+// Start the web UI with `--api --cors-allow-origins=<this page's origin>`.
+// With `--api-auth user:pass`, enter "user:pass" as the API key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Automatic1111 {
+    base: String,
+    token: String, // "user:pass" for --api-auth, empty otherwise
+}
+
+impl Automatic1111 {
+    fn request(&self, r: RequestBuilder) -> RequestBuilder {
+        let basic = web_sys::window().and_then(|w| w.btoa(&self.token).ok());
+        match basic {
+            Some(b) if self.token.contains(':') => r.header("Authorization", &format!("Basic {b}")),
+            _ => r,
+        }
+    }
+}
+
+impl ImageBackend for Automatic1111 {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            models: &[ImageModel::Sdxl],
+            setup: "Start the web UI with --api --cors-allow-origins=<this page's origin>.",
+            original_ext: "png",
+            // the web UI renders on after the browser gives up; a retry would queue a second render
+            retry_timeouts: false,
+        }
+    }
+
+    async fn generate(
+        &self,
+        job: &GenJob<'_>,
+        signal: Option<&AbortSignal>,
+    ) -> Result<GeneratedImage, GenError> {
+        let st = job.settings;
+        let (prompt, negative) = worker_equivalent(job);
+        let body = json!({
+            "prompt": prompt,
+            "negative_prompt": negative,
+            "width": st.width,
+            "height": st.height,
+            "steps": st.num_steps,
+            "cfg_scale": st.guidance,
+            "seed": job.seed.map(i64::from).unwrap_or(-1),
+        });
+        let call = RequestTimeout::start(request_timeout_secs(st), signal);
+        let r = self.request(Request::post(&format!("{}/sdapi/v1/txt2img", self.base)));
+        let resp =
+            call.check(send(json_body(r.abort_signal(call.signal().as_ref()), &body)?).await)?;
+        let out = call.check(read_json(resp).await)?;
+
+        let b64 = out["images"][0].as_str().ok_or(GenError::EmptyBody)?;
+        // `info` is a JSON document inside a string.
+        let info = out["info"]
+            .as_str()
+            .and_then(|s| serde_json::from_str::<Value>(s).ok())
+            .unwrap_or_default();
+        Ok(GeneratedImage {
+            bytes: decode_base64(b64)?,
+            mime: "image/png",
+            meta: ImageMeta {
+                model: info["sd_model_name"].as_str().map(str::to_string),
+                steps: Some(st.num_steps),
+                guidance: Some(st.guidance as f64),
+                style: Some(job.worker_style.to_string()),
+                prompt_chars: Some(js_length(&prompt) as u32),
+                elapsed_ms: None,
+            },
+        })
+    }
+
+    async fn health(&self, signal: Option<&AbortSignal>) -> Result<String, GenError> {
+        let r = self.request(Request::get(&format!("{}/sdapi/v1/options", self.base)));
+        let options = read_json(send(build(r.abort_signal(signal))?).await?).await?;
+        Ok(match options["sd_model_checkpoint"].as_str() {
+            Some(m) => format!("Automatic1111 — checkpoint {m}"),
+            None => "Automatic1111".to_string(),
+        })
+    }
+}
+
+// ----------------------------
+// ComfyUI (/prompt, /history, /view)
+// ----------------------------
+// Start ComfyUI with `--enable-cors-header <this page's origin>`. We queue a
+// plain txt2img graph with the first installed checkpoint, then poll history
+// until the image is saved and fetch it. The request timeout applies to each
+// call, not to the wait in ComfyUI's queue; that wait ends after
+// COMFY_MAX_POLLS. A prompt we stop waiting for is taken off the server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComfyUi {
+    base: String,
+}
+
+impl ComfyUi {
+    async fn get_json(&self, path: &str, signal: Option<&AbortSignal>) -> Result<Value, GenError> {
+        let r = Request::get(&format!("{}{path}", self.base)).abort_signal(signal);
+        read_json(send(build(r)?).await?).await
+    }
+
+    async fn checkpoint(&self, signal: Option<&AbortSignal>) -> Result<String, GenError> {
+        let info = self
+            .get_json("/object_info/CheckpointLoaderSimple", signal)
+            .await?;
+        info["CheckpointLoaderSimple"]["input"]["required"]["ckpt_name"][0][0]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| GenError::Setup("ComfyUI has no checkpoints installed".into()))
+    }
+
+    /// Saved image of prompt `id`, polling history until ComfyUI has run it.
+    async fn wait_for(
+        &self,
+        id: &str,
+        secs: u32,
+        signal: Option<&AbortSignal>,
+    ) -> Result<Value, GenError> {
+        for _ in 0..COMFY_MAX_POLLS {
+            let call = RequestTimeout::start(secs, signal);
+            let history = call.check(
+                self.get_json(&format!("/history/{id}"), call.signal().as_ref())
+                    .await,
+            )?;
+            if let Some(found) = history[id]["outputs"]["7"]["images"].get(0) {
+                return Ok(found.clone());
+            }
+            // Finished without an image: a failed node, out of memory, a broken checkpoint
+            let status = &history[id]["status"];
+            if status["status_str"] == "error" || status["completed"] == true {
+                return Err(GenError::Setup(comfy_failure(id, status)));
+            }
+            if signal.map(|s| s.aborted()).unwrap_or(false) {
+                return Err(GenError::Network("aborted".into()));
+            }
+            sleep_ms(COMFY_POLL_MS, signal).await;
+        }
+        Err(GenError::Timeout(COMFY_MAX_POLLS * COMFY_POLL_MS / 1000))
+    }
+
+    /// Take prompt `id` off the server: out of the queue, or interrupted if it
+    /// is already rendering. Best effort, so a retry doesn't render it twice.
+    async fn forget(&self, id: &str) {
+        let call = RequestTimeout::start(COMFY_CLEANUP_SECS, None);
+        let post = |path: &str, body: Value| {
+            Request::post(&format!("{}{path}", self.base))
+                .abort_signal(call.signal().as_ref())
+                .json(&body)
+        };
+        if let Ok(req) = post("/queue", json!({ "delete": [id] })) {
+            let _ = req.send().await;
+        }
+        let Ok(queue) = self.get_json("/queue", call.signal().as_ref()).await else {
+            return;
+        };
+        let running = queue["queue_running"]
+            .as_array()
+            .map(|r| r.iter().any(|p| p[1].as_str() == Some(id)))
+            .unwrap_or(false);
+        if running {
+            // ComfyUI versions without per-prompt interrupt stop whatever runs, which is ours
+            if let Ok(req) = post("/interrupt", json!({ "prompt_id": id })) {
+                let _ = req.send().await;
+            }
+        }
+    }
+}
+
+/// What ComfyUI's history says went wrong with prompt `id`.
+fn comfy_failure(id: &str, status: &Value) -> String {
+    let error = status["messages"]
+        .as_array()
+        .and_then(|m| m.iter().find(|m| m[0] == "execution_error"))
+        .map(|m| &m[1]);
+    match error {
+        Some(e) => format!(
+            "ComfyUI failed in {}: {}",
+            e["node_type"].as_str().unwrap_or("a node"),
+            e["exception_message"]
+                .as_str()
+                .unwrap_or("no message")
+                .trim()
+        ),
+        None => format!("ComfyUI finished prompt {id} without an image"),
+    }
+}
+
+/// Minimal txt2img graph: checkpoint → prompts → sampler → decode → save.
+fn comfy_graph(ckpt: &str, prompt: &str, negative: &str, seed: u32, st: &GenSettings) -> Value {
+    json!({
+        "1": { "class_type": "CheckpointLoaderSimple", "inputs": { "ckpt_name": ckpt } },
+        "2": { "class_type": "CLIPTextEncode", "inputs": { "text": prompt, "clip": ["1", 1] } },
+        "3": { "class_type": "CLIPTextEncode", "inputs": { "text": negative, "clip": ["1", 1] } },
+        "4": { "class_type": "EmptyLatentImage", "inputs": { "width": st.width, "height": st.height, "batch_size": 1 } },
+        "5": { "class_type": "KSampler", "inputs": {
+            "model": ["1", 0], "positive": ["2", 0], "negative": ["3", 0], "latent_image": ["4", 0],
+            "seed": seed, "steps": st.num_steps, "cfg": st.guidance,
+            "sampler_name": "euler", "scheduler": "normal", "denoise": 1.0
+        } },
+        "6": { "class_type": "VAEDecode", "inputs": { "samples": ["5", 0], "vae": ["1", 2] } },
+        "7": { "class_type": "SaveImage", "inputs": { "images": ["6", 0], "filename_prefix": "ebook" } }
+    })
+}
+
+impl ImageBackend for ComfyUi {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            models: &[ImageModel::Sdxl],
+            setup: "Start ComfyUI with --enable-cors-header <this page's origin>.",
+            original_ext: "png",
+            retry_timeouts: true, // a prompt we give up on is taken off the server first
+        }
+    }
+
+    async fn generate(
+        &self,
+        job: &GenJob<'_>,
+        signal: Option<&AbortSignal>,
+    ) -> Result<GeneratedImage, GenError> {
+        let st = job.settings;
+        let secs = request_timeout_secs(st);
+        let call = RequestTimeout::start(secs, signal);
+        let ckpt = call.check(self.checkpoint(call.signal().as_ref()).await)?;
+        let (prompt, negative) = worker_equivalent(job);
+        let graph = comfy_graph(&ckpt, &prompt, &negative, seed_or_random(job.seed), st);
+
+        let call = RequestTimeout::start(secs, signal);
+        let r =
+            Request::post(&format!("{}/prompt", self.base)).abort_signal(call.signal().as_ref());
+        let queued = call.check(
+            async { read_json(send(json_body(r, &json!({ "prompt": graph }))?).await?).await }
+                .await,
+        )?;
+        let id = queued["prompt_id"]
+            .as_str()
+            .ok_or_else(|| GenError::Decode("ComfyUI did not return a prompt_id".into()))?
+            .to_string();
+
+        let image = match self.wait_for(&id, secs, signal).await {
+            Ok(image) => image,
+            Err(e) => {
+                self.forget(&id).await;
+                return Err(e);
+            }
+        };
+
+        let query = ["filename", "subfolder", "type"]
+            .iter()
+            .map(|k| {
+                let v = image[*k].as_str().unwrap_or_default();
+                format!("{k}={}", String::from(js_sys::encode_uri_component(v)))
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        let call = RequestTimeout::start(secs, signal);
+        let r = Request::get(&format!("{}/view?{query}", self.base))
+            .abort_signal(call.signal().as_ref());
+        Ok(GeneratedImage {
+            bytes: call.check(async { read_bytes(send(build(r)?).await?).await }.await)?,
+            mime: "image/png",
+            meta: ImageMeta {
+                model: Some(ckpt),
+                steps: Some(st.num_steps),
+                guidance: Some(st.guidance as f64),
+                style: Some(job.worker_style.to_string()),
+                prompt_chars: Some(js_length(&prompt) as u32),
+                elapsed_ms: None,
+            },
+        })
+    }
+
+    async fn health(&self, signal: Option<&AbortSignal>) -> Result<String, GenError> {
+        let stats = self.get_json("/system_stats", signal).await?;
+        let version = stats["system"]["comfyui_version"].as_str().unwrap_or("?");
+        let ckpt = self.checkpoint(signal).await?;
+        Ok(format!("ComfyUI {version} — checkpoint {ckpt}"))
+    }
+}
+
+// ----------------------------
+// Offline placeholders
+// ----------------------------
+// No server and no AI quota: a gradient picked from the slot key (same key,
+// same image), a rule-of-thirds grid, the 16:9 crop frame and a crop-safe
+// margin. Good for page layout, exports and repeatable post-processing input.
+
+const PLACEHOLDER_SAFE_MARGIN: f64 = 0.1; // share of the 16:9 frame kept clear on each side
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder;
+
+/// FNV-1a, so colors never change between runs or builds.
+fn key_hash(key: &str) -> u32 {
+    key.bytes()
+        .fold(0x811c_9dc5, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// The two gradient hues for `key`, in degrees.
+fn placeholder_hues(key: &str) -> (u32, u32) {
+    let hash = key_hash(key);
+    let hue = hash % 360;
+    (hue, (hue + 40 + (hash >> 9) % 100) % 360)
+}
+
+fn draw_placeholder(key: &str, w: u32, h: u32) -> Result<Vec<u8>, String> {
+    let (canvas, ctx) = canvas_2d(w, h)?;
+    let (fw, fh) = (w as f64, h as f64);
+    let line = (fw / 600.0).max(1.0);
+
+    // Gradient seeded from the key
+    let (hue, hue2) = placeholder_hues(key);
+    let gradient = ctx.create_linear_gradient(0.0, 0.0, fw, fh);
+    gradient
+        .add_color_stop(0.0, &format!("hsl({hue}, 60%, 72%)"))
+        .and_then(|_| gradient.add_color_stop(1.0, &format!("hsl({hue2}, 55%, 42%)")))
+        .map_err(|_| "add_color_stop failed")?;
+    ctx.set_fill_style_canvas_gradient(&gradient);
+    ctx.fill_rect(0.0, 0.0, fw, fh);
+
+    // Area the 16:9 download crops away
+    let (cx, cy, cw, ch) = crop_16x9(fw, fh);
+    ctx.set_fill_style_str("rgba(0, 0, 0, 0.3)");
+    ctx.fill_rect(0.0, 0.0, fw, cy);
+    ctx.fill_rect(0.0, cy + ch, fw, fh - cy - ch);
+    ctx.fill_rect(0.0, cy, cx, ch);
+    ctx.fill_rect(cx + cw, cy, fw - cx - cw, ch);
+
+    // Rule-of-thirds grid inside the 16:9 frame
+    ctx.set_stroke_style_str("rgba(255, 255, 255, 0.45)");
+    ctx.set_line_width(line);
+    ctx.begin_path();
+    for i in 1..3 {
+        let x = cx + cw * i as f64 / 3.0;
+        let y = cy + ch * i as f64 / 3.0;
+        ctx.move_to(x, cy);
+        ctx.line_to(x, cy + ch);
+        ctx.move_to(cx, y);
+        ctx.line_to(cx + cw, y);
+    }
+    ctx.stroke();
+
+    // Crop-safe area (dashed)
+    let (mx, my) = (cw * PLACEHOLDER_SAFE_MARGIN, ch * PLACEHOLDER_SAFE_MARGIN);
+    let dash = js_sys::Array::of2(&(line * 8.0).into(), &(line * 6.0).into());
+    ctx.set_line_dash(&dash)
+        .map_err(|_| "set_line_dash failed")?;
+    ctx.set_stroke_style_str("rgba(255, 255, 255, 0.9)");
+    ctx.set_line_width(line * 2.0);
+    ctx.stroke_rect(cx + mx, cy + my, cw - 2.0 * mx, ch - 2.0 * my);
+    ctx.set_line_dash(&js_sys::Array::new())
+        .map_err(|_| "set_line_dash failed")?;
+
+    // Label
+    ctx.set_fill_style_str("rgba(255, 255, 255, 0.95)");
+    ctx.set_text_align("center");
+    ctx.set_text_baseline("middle");
+    ctx.set_font(&format!(
+        "600 {}px system-ui, sans-serif",
+        (ch / 12.0).round()
+    ));
+    ctx.fill_text(&pretty_slot_name(key), fw / 2.0, fh / 2.0)
+        .map_err(|_| "fill_text failed")?;
+    ctx.set_font(&format!("{}px system-ui, sans-serif", (ch / 28.0).round()));
+    ctx.fill_text(
+        &format!("{w}×{h} placeholder"),
+        fw / 2.0,
+        fh / 2.0 + ch / 10.0,
+    )
+    .map_err(|_| "fill_text failed")?;
+
+    let url = canvas
+        .to_data_url()
+        .map_err(|_| "to_data_url failed".to_string())?;
+    let b64 = url.split_once(',').map(|(_, b)| b).unwrap_or_default();
+    decode_base64(b64).map_err(|e| e.to_string())
+}
+
+impl ImageBackend for Placeholder {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            models: &ImageModel::ALL,
+            setup: "No server needed: placeholders are drawn in the browser at the SDXL width × height.",
+            original_ext: "png",
+            retry_timeouts: true,
+        }
+    }
+
+    async fn generate(
+        &self,
+        job: &GenJob<'_>,
+        _signal: Option<&AbortSignal>,
+    ) -> Result<GeneratedImage, GenError> {
+        let st = job.settings;
+        let bytes = draw_placeholder(job.key, st.width, st.height).map_err(GenError::Canvas)?;
+        Ok(GeneratedImage {
+            bytes,
+            mime: "image/png",
+            meta: ImageMeta {
+                model: Some("placeholder".to_string()),
+                style: Some(job.worker_style.to_string()),
+                ..ImageMeta::default()
+            },
+        })
+    }
+
+    async fn health(&self, _signal: Option<&AbortSignal>) -> Result<String, GenError> {
+        Ok("offline placeholders, no server involved".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholder_colors_are_pinned() {
+        assert_eq!(key_hash("cover"), 0xbacd_0480);
+        assert_eq!(key_hash("ch1"), 0x4d86_14ed);
+        assert_eq!(placeholder_hues("cover"), (208, 338));
+        assert_eq!(placeholder_hues("ch1"), (333, 111));
+        assert_eq!(placeholder_hues("credits"), (93, 183));
+    }
+}
@@ -0,0 +1,429 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use web_sys::{AbortController, AbortSignal, AddEventListenerOptions, Url};
+use yew::Callback;
+
+use crate::backend::{Backend, GenJob, ImageBackend};
+use crate::errors::{GenError, SlotError};
+use crate::settings::{GenSettings, ATTEMPTS_MAX, CONCURRENCY_MAX, VARIANTS_MAX};
+use crate::slots::pretty_slot_name;
+use crate::{
+    bytes_to_object_url, make_16x9_png_object_url, original_filename, slot_style, variant_seed,
+    Candidate, PromptItem, RenderedImage,
+};
+
+// ----------------------------
+// Batch generation (work queue, N requests in flight)
+// ----------------------------
+// A fixed number of workers pull jobs (slot, candidate) off a shared queue.
+// Results are stored by slot and candidate index, so the gallery stays in
+// slot order no matter which request finishes first. Backends put the request
+// timeout on each HTTP call and listen to the batch's abort signal, which
+// Cancel fires after draining the queue. Pause lets in-flight requests finish
+// but starts nothing new until resumed.
+
+const PAUSE_POLL_MS: u32 = 250;
+
+// ----------------------------
+// Errors and retry policy
+// ----------------------------
+const BACKOFF_BASE_MS: u32 = 1_000;
+const BACKOFF_MAX_MS: u32 = 30_000;
+
+enum Retry {
+    No,
+    Backoff,
+    After(u32), // server-requested delay in ms
+}
+
+/// Whether another attempt is worth it for `err`.
+fn retry_advice(err: &GenError) -> Retry {
+    match err {
+        GenError::Network(_) | GenError::Timeout(_) | GenError::EmptyBody => Retry::Backoff,
+        GenError::Http {
+            status: 429,
+            retry_after_ms,
+            ..
+        } => retry_after_ms.map(Retry::After).unwrap_or(Retry::Backoff),
+        // 5xx includes the Worker's 502 "Upstream AI error"
+        GenError::Http { status, .. } if *status >= 500 || *status == 408 => Retry::Backoff,
+        // 400 bad request, 401 bad key, other 4xx and local failures: same answer next time
+        GenError::Http { .. }
+        | GenError::Auth { .. }
+        | GenError::Setup(_)
+        | GenError::Decode(_)
+        | GenError::Canvas(_)
+        | GenError::Cancelled => Retry::No,
+    }
+}
+
+/// Exponential backoff with jitter: half the step plus a random share of the other half.
+fn backoff_ms(attempt: u32) -> u32 {
+    let step = BACKOFF_BASE_MS
+        .saturating_mul(1 << attempt.saturating_sub(1).min(5))
+        .min(BACKOFF_MAX_MS);
+    step / 2 + (js_sys::Math::random() * (step / 2) as f64) as u32
+}
+
+/// Wait `ms`, or less if `signal` aborts first.
+pub async fn sleep_ms(ms: u32, signal: Option<&AbortSignal>) {
+    let mut wake = None;
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let scheduled = web_sys::window().and_then(|w| {
+            w.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32)
+                .ok()
+        });
+        if let Some(signal) = signal {
+            let once = AddEventListenerOptions::new();
+            once.set_once(true);
+            let _ = signal.add_event_listener_with_callback_and_add_event_listener_options(
+                "abort", &resolve, &once,
+            );
+        }
+        if scheduled.is_none() {
+            let _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+        }
+        wake = Some(resolve);
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+    // The timer won: stop listening, or every wait leaves a listener on the batch signal.
+    if let (Some(signal), Some(wake)) = (signal, wake) {
+        let _ = signal.remove_event_listener_with_callback("abort", &wake);
+    }
+}
+
+// ----------------------------
+// One candidate
+// ----------------------------
+/// Generate one candidate: ask the backend, then build the preview and 16:9 PNG URLs.
+async fn generate_candidate(
+    backend: &Backend,
+    item: &PromptItem,
+    seed: Option<u32>,
+    settings: &GenSettings,
+    signal: Option<&AbortSignal>,
+) -> Result<Candidate, GenError> {
+    let job = GenJob {
+        key: &item.key,
+        prompt: &item.prompt,
+        worker_style: slot_style(item, settings).worker_style,
+        seed,
+        settings,
+    };
+    let started = js_sys::Date::now();
+    let image = backend.generate(&job, signal).await?;
+    let mut meta = image.meta;
+    meta.elapsed_ms = Some((js_sys::Date::now() - started) as u32);
+
+    // Preview URL (as delivered)
+    let preview_url = bytes_to_object_url(&image.bytes, image.mime).map_err(GenError::Decode)?;
+
+    // 16:9 PNG download (1600x900); a JPEG under a .png name would be worse than an error
+    let png_url = match make_16x9_png_object_url(&preview_url, 1600, 900).await {
+        Ok(u) => u,
+        Err(e) => {
+            let _ = Url::revoke_object_url(&preview_url);
+            return Err(e);
+        }
+    };
+
+    Ok(Candidate {
+        preview_url,
+        download_url: png_url,
+        seed,
+        meta,
+    })
+}
+
+/// `generate_candidate` with retries: backoff for transient failures and
+/// timeouts, the server's Retry-After on 429, and no retry for errors that
+/// would just repeat.
+async fn generate_with_retry(run: &BatchRun, job: Job) -> Result<Candidate, GenError> {
+    let item = &run.items[job.slot];
+    let seed = variant_seed(item.seed, job.variant);
+    let max = run.settings.max_attempts.clamp(1, ATTEMPTS_MAX);
+    let retry_timeouts = run.backend.capabilities().retry_timeouts;
+    let mut attempt = 1;
+    let cancel_signal = run.abort.as_ref().map(|a| a.signal());
+    loop {
+        let result = generate_candidate(
+            &run.backend,
+            item,
+            seed,
+            &run.settings,
+            cancel_signal.as_ref(),
+        )
+        .await;
+        let err = match result {
+            Ok(image) => return Ok(image),
+            Err(_) if run.cancelled.get() => return Err(GenError::Cancelled),
+            Err(e) => e,
+        };
+        let wait = match retry_advice(&err) {
+            _ if attempt >= max => return Err(err),
+            _ if matches!(err, GenError::Timeout(_)) && !retry_timeouts => return Err(err),
+            Retry::No => return Err(err),
+            Retry::Backoff => backoff_ms(attempt),
+            Retry::After(ms) => ms,
+        };
+        attempt += 1;
+        run.cb.on_status.emit(format!(
+            "{}: {err} — retrying in {:.1}s (attempt {attempt}/{max})…",
+            run.job_name(job),
+            wait as f64 / 1000.0
+        ));
+        sleep_ms(wait, cancel_signal.as_ref()).await;
+        if run.cancelled.get() {
+            return Err(GenError::Cancelled);
+        }
+        run.set_attempt(job, attempt);
+    }
+}
+
+// ----------------------------
+// Batch run
+// ----------------------------
+/// Where a batch reports to: finished images (in slot order), failures so far,
+/// progress text, and a final summary once every worker has stopped.
+pub struct BatchCallbacks {
+    pub on_images: Callback<Vec<RenderedImage>>,
+    pub on_errors: Callback<Vec<SlotError>>,
+    pub on_status: Callback<String>,
+    pub on_done: Callback<String>,
+}
+
+/// One request's worth of work: candidate `variant` of slot `slot`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Job {
+    slot: usize,
+    variant: u32,
+}
+
+struct BatchRun {
+    items: Vec<PromptItem>,
+    settings: GenSettings,
+    backend: Backend,
+    variants: u32,
+    queue: RefCell<VecDeque<Job>>,
+    in_flight: RefCell<Vec<(Job, u32)>>, // job, attempt number
+    results: RefCell<Vec<Vec<Option<Candidate>>>>, // [slot][variant]
+    errors: RefCell<Vec<SlotError>>,
+    finished: Cell<usize>,          // jobs done, successful or not
+    workers: Cell<usize>,           // workers still running
+    abort: Option<AbortController>, // Cancel: aborts requests in flight, wakes retry waits
+    cancelled: Cell<bool>,
+    paused: Cell<bool>,
+    cb: BatchCallbacks,
+}
+
+impl BatchRun {
+    fn total(&self) -> usize {
+        self.items.len() * self.variants as usize
+    }
+
+    /// Slots with at least one finished candidate, in slot order.
+    fn images(&self) -> Vec<RenderedImage> {
+        let results = self.results.borrow();
+        let ext = self.backend.capabilities().original_ext;
+        self.items
+            .iter()
+            .zip(results.iter())
+            .filter_map(|(item, found)| {
+                let candidates = found.iter().flatten().cloned().collect::<Vec<_>>();
+                (!candidates.is_empty()).then(|| RenderedImage {
+                    slot: item.id,
+                    key: item.key.clone(),
+                    preview_filename: original_filename(item, ext),
+                    download_filename: format!("{}.png", item.key),
+                    candidates,
+                    chosen: 0,
+                })
+            })
+            .collect()
+    }
+
+    fn job_name(&self, job: Job) -> String {
+        let name = pretty_slot_name(&self.items[job.slot].key);
+        if self.variants > 1 {
+            format!("{name} #{}", job.variant + 1)
+        } else {
+            name
+        }
+    }
+
+    fn set_attempt(&self, job: Job, attempt: u32) {
+        for running in self.in_flight.borrow_mut().iter_mut() {
+            if running.0 == job {
+                running.1 = attempt;
+            }
+        }
+        self.report();
+    }
+
+    fn report(&self) {
+        let mut in_flight = self.in_flight.borrow().clone();
+        in_flight.sort_unstable();
+        let names = in_flight
+            .iter()
+            .map(|&(job, attempt)| match attempt {
+                1 => self.job_name(job),
+                n => format!("{} (attempt {n})", self.job_name(job)),
+            })
+            .collect::<Vec<_>>();
+        let (done, total) = (self.finished.get(), self.total());
+        let msg = match (self.cancelled.get(), self.paused.get(), names.is_empty()) {
+            (true, _, _) => format!("Canceling — {done}/{total} done…"),
+            (false, true, true) => format!("Paused — {done}/{total} done. Resume to continue."),
+            (false, true, false) => format!(
+                "Pausing — letting {} finish, {done}/{total} done…",
+                names.join(", ")
+            ),
+            (false, false, _) => format!(
+                "Generating {} — {} in flight, {done}/{total} done…",
+                names.join(", "),
+                names.len()
+            ),
+        };
+        self.cb.on_status.emit(msg);
+    }
+
+    fn summary(&self) -> String {
+        let errors = self.errors.borrow();
+        let kept = self.results.borrow().iter().flatten().flatten().count();
+        if self.cancelled.get() {
+            return format!("Canceled — kept {kept} of {} images.", self.total());
+        }
+        match errors.last() {
+            None => "Done ✅".to_string(),
+            Some(last) => format!(
+                "Done — {kept} of {} images; {} failed (last: {}: {})",
+                self.total(),
+                errors.len(),
+                pretty_slot_name(&last.key),
+                last.error
+            ),
+        }
+    }
+}
+
+async fn worker(run: Rc<BatchRun>) {
+    loop {
+        while run.paused.get() && !run.cancelled.get() {
+            sleep_ms(PAUSE_POLL_MS, None).await;
+        }
+        let Some(job) = run.queue.borrow_mut().pop_front() else {
+            break;
+        };
+        run.in_flight.borrow_mut().push((job, 1));
+        run.report();
+
+        let result = generate_with_retry(&run, job).await;
+
+        let attempts = run
+            .in_flight
+            .borrow()
+            .iter()
+            .find(|&&(j, _)| j == job)
+            .map(|&(_, n)| n)
+            .unwrap_or(1);
+        run.in_flight.borrow_mut().retain(|&(j, _)| j != job);
+        run.finished.set(run.finished.get() + 1);
+        match result {
+            Ok(candidate) => {
+                run.results.borrow_mut()[job.slot][job.variant as usize] = Some(candidate);
+                run.cb.on_images.emit(run.images());
+            }
+            Err(GenError::Cancelled) => {}
+            Err(error) => {
+                run.errors.borrow_mut().push(SlotError {
+                    slot: run.items[job.slot].id,
+                    key: run.items[job.slot].key.clone(),
+                    variant: (run.variants > 1).then_some(job.variant),
+                    error,
+                    attempts,
+                    at: js_sys::Date::now(),
+                });
+                run.cb.on_errors.emit(run.errors.borrow().clone());
+            }
+        }
+        run.report();
+    }
+
+    run.workers.set(run.workers.get() - 1);
+    if run.workers.get() == 0 {
+        run.cb.on_done.emit(run.summary());
+    }
+}
+
+/// Controls for a running batch.
+#[derive(Clone)]
+pub struct BatchHandle(Rc<BatchRun>);
+
+impl BatchHandle {
+    /// Abort in-flight requests and drop everything still queued.
+    /// Finished images are kept; `on_done` fires once the workers stop.
+    pub fn cancel(&self) {
+        let run = &self.0;
+        run.cancelled.set(true);
+        run.paused.set(false);
+        run.queue.borrow_mut().clear();
+        if let Some(abort) = &run.abort {
+            abort.abort();
+        }
+        run.report();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.0.workers.get() > 0
+    }
+
+    /// Paused batches finish what is in flight but start nothing new.
+    pub fn set_paused(&self, paused: bool) {
+        if !self.0.cancelled.get() {
+            self.0.paused.set(paused);
+            self.0.report();
+        }
+    }
+}
+
+/// Generate `variants` candidates for every item with at most `concurrency`
+/// requests in flight.
+pub fn start_batch(
+    items: Vec<PromptItem>,
+    settings: GenSettings,
+    backend: Backend,
+    cb: BatchCallbacks,
+) -> BatchHandle {
+    let variants = settings.variants.clamp(1, VARIANTS_MAX);
+    let jobs = (0..items.len())
+        .flat_map(|slot| (0..variants).map(move |variant| Job { slot, variant }))
+        .collect::<VecDeque<_>>();
+    let workers = (settings.concurrency as usize)
+        .clamp(1, CONCURRENCY_MAX as usize)
+        .min(jobs.len());
+
+    let run = Rc::new(BatchRun {
+        variants,
+        queue: RefCell::new(jobs),
+        in_flight: RefCell::new(vec![]),
+        results: RefCell::new(vec![vec![None; variants as usize]; items.len()]),
+        errors: RefCell::new(vec![]),
+        finished: Cell::new(0),
+        workers: Cell::new(workers),
+        abort: AbortController::new().ok(),
+        cancelled: Cell::new(false),
+        paused: Cell::new(false),
+        items,
+        settings,
+        backend,
+        cb,
+    });
+    if workers == 0 {
+        run.cb.on_done.emit("Nothing to generate.".to_string());
+    }
+    for _ in 0..workers {
+        wasm_bindgen_futures::spawn_local(worker(run.clone()));
+    }
+    BatchHandle(run)
+}
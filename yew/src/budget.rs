@@ -0,0 +1,262 @@
+use crate::worker_prompt::{floor_utf16_boundary, js_length};
+
+// ----------------------------
+// Prompt budget (priority-aware fitting)
+// ----------------------------
+// Prompts are assembled from tagged segments. When the total is over budget,
+// segments are visited lowest priority first: each is shortened to its minimum
+// and, if still over and optional, dropped. Only when everything optional is
+// gone are premise/scene cut below their minimum. Every cut is reported so
+// the UI can explain it.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentTag {
+    Premise,
+    Scene,
+    Characters,
+    Composition,
+    Style,
+    Safety,
+}
+
+impl SegmentTag {
+    pub fn label(self) -> &'static str {
+        match self {
+            SegmentTag::Premise => "Premise",
+            SegmentTag::Scene => "Scene",
+            SegmentTag::Characters => "Characters",
+            SegmentTag::Composition => "Composition",
+            SegmentTag::Style => "Style",
+            SegmentTag::Safety => "Safety",
+        }
+    }
+
+    /// Higher keeps its text longer.
+    fn priority(self) -> u8 {
+        match self {
+            SegmentTag::Premise => 6,
+            SegmentTag::Scene => 5,
+            SegmentTag::Characters => 4,
+            SegmentTag::Style => 3,
+            SegmentTag::Composition => 2,
+            SegmentTag::Safety => 1,
+        }
+    }
+
+    /// Shortest useful length before the segment is dropped instead.
+    fn min_units(self) -> usize {
+        match self {
+            SegmentTag::Premise => 120,
+            SegmentTag::Scene => 80,
+            SegmentTag::Characters => 120,
+            SegmentTag::Style => 60,
+            SegmentTag::Composition => 0,
+            SegmentTag::Safety => 0,
+        }
+    }
+
+    /// Premise and scene are never dropped outright.
+    fn required(self) -> bool {
+        matches!(self, SegmentTag::Premise | SegmentTag::Scene)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub tag: SegmentTag,
+    pub text: String,
+}
+
+impl Segment {
+    pub fn new(tag: SegmentTag, text: impl Into<String>) -> Self {
+        Segment {
+            tag,
+            text: text.into().trim().to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CutAction {
+    Shortened { from: usize, to: usize },
+    Dropped { from: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetCut {
+    pub tag: SegmentTag,
+    pub action: CutAction,
+    pub reason: String,
+}
+
+impl BudgetCut {
+    pub fn describe(&self) -> String {
+        match self.action {
+            CutAction::Shortened { from, to } => {
+                format!(
+                    "{} shortened {from} → {to} units: {}",
+                    self.tag.label(),
+                    self.reason
+                )
+            }
+            CutAction::Dropped { from } => {
+                format!(
+                    "{} dropped ({from} units): {}",
+                    self.tag.label(),
+                    self.reason
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Assembled {
+    pub text: String,
+    pub cuts: Vec<BudgetCut>,
+}
+
+/// Lengths are UTF-16 code units, the unit the Worker budgets in.
+fn len(s: &str) -> usize {
+    js_length(s)
+}
+
+fn joined_len(segs: &[Segment]) -> usize {
+    let parts = segs.iter().filter(|s| !s.text.is_empty());
+    let (chars, count) = parts.fold((0usize, 0usize), |(c, n), s| (c + len(&s.text), n + 1));
+    chars + count.saturating_sub(1) // single spaces between segments
+}
+
+/// Cut `text` to at most `max` units, backing up to a word boundary when possible.
+fn shorten(text: &str, max: usize) -> String {
+    if len(text) <= max {
+        return text.to_string();
+    }
+    let head = &text[..floor_utf16_boundary(text, max)];
+    let head = match head.rfind(char::is_whitespace) {
+        Some(i) if i > 0 => &head[..i],
+        _ => head,
+    };
+    head.trim_end_matches([',', ';', ':', ' ']).to_string()
+}
+
+/// Join segments in order, fitting them into `budget` UTF-16 units by priority.
+pub fn assemble(mut segs: Vec<Segment>, budget: usize) -> Assembled {
+    let mut cuts = vec![];
+
+    // Lowest priority first; later segments of the same tag go first.
+    let mut order = (0..segs.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| (segs[i].tag.priority(), std::cmp::Reverse(i)));
+
+    // 1) lowest priority first: shorten to the minimum, then drop if optional
+    for &i in &order {
+        let over = joined_len(&segs).saturating_sub(budget);
+        if over == 0 {
+            break;
+        }
+        let from = len(&segs[i].text);
+        let min = segs[i].tag.min_units();
+        if from == 0 {
+            continue;
+        }
+
+        if from > min && min > 0 {
+            segs[i].text = shorten(&segs[i].text, from.saturating_sub(over).max(min));
+        }
+        let still_over = joined_len(&segs).saturating_sub(budget);
+        if still_over > 0 && !segs[i].tag.required() {
+            segs[i].text.clear();
+            cuts.push(BudgetCut {
+                tag: segs[i].tag,
+                action: CutAction::Dropped { from },
+                reason: format!("over budget by {over} units; lower priority than what remains"),
+            });
+            continue;
+        }
+
+        let to = len(&segs[i].text);
+        if to < from {
+            cuts.push(BudgetCut {
+                tag: segs[i].tag,
+                action: CutAction::Shortened { from, to },
+                reason: format!("over budget by {over} units"),
+            });
+        }
+    }
+
+    // 2) last resort: cut required segments below their minimum
+    for &i in &order {
+        let over = joined_len(&segs).saturating_sub(budget);
+        if over == 0 {
+            break;
+        }
+        let from = len(&segs[i].text);
+        if from == 0 {
+            continue;
+        }
+        let text = shorten(&segs[i].text, from.saturating_sub(over));
+        let to = len(&text);
+        segs[i].text = text;
+        cuts.push(BudgetCut {
+            tag: segs[i].tag,
+            action: CutAction::Shortened { from, to },
+            reason: format!("still over budget by {over} units with everything else at minimum"),
+        });
+    }
+
+    let text = segs
+        .iter()
+        .filter(|s| !s.text.is_empty())
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Assembled { text, cuts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_lands_on_emoji_boundary() {
+        let out = assemble(
+            vec![Segment::new(SegmentTag::Premise, "😀".repeat(100))],
+            101,
+        );
+        assert_eq!(out.text, "😀".repeat(50));
+        assert_eq!(js_length(&out.text), 100);
+    }
+
+    #[test]
+    fn lowest_priority_segments_drop_first() {
+        let segs = vec![
+            Segment::new(SegmentTag::Premise, "p".repeat(150)),
+            Segment::new(SegmentTag::Scene, "s".repeat(100)),
+            Segment::new(SegmentTag::Composition, "c".repeat(50)),
+            Segment::new(SegmentTag::Safety, "x".repeat(50)),
+        ];
+        let out = assemble(segs, 260);
+
+        let dropped = out
+            .cuts
+            .iter()
+            .map(|c| (c.tag, matches!(c.action, CutAction::Dropped { .. })))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dropped,
+            [(SegmentTag::Safety, true), (SegmentTag::Composition, true)]
+        );
+        assert_eq!(out.text, format!("{} {}", "p".repeat(150), "s".repeat(100)));
+    }
+
+    #[test]
+    fn fits_untouched() {
+        let segs = vec![
+            Segment::new(SegmentTag::Premise, "A fox"),
+            Segment::new(SegmentTag::Scene, "in the snow"),
+        ];
+        let out = assemble(segs, 100);
+        assert!(out.cuts.is_empty());
+        assert_eq!(out.text, "A fox in the snow");
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+// ----------------------------
+// Character bible (consistent look across every slot)
+// ----------------------------
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Character {
+    pub id: u32,
+    pub name: String,
+    pub species: String,  // "red fox", "girl"...
+    pub age: String,      // "about 8 years old"
+    pub clothing: String, // "green hooded cloak, brown boots"
+    pub colors: String,   // "rust-orange fur, cream chest"
+    pub features: String, // "round glasses, white-tipped tail"
+}
+
+impl Character {
+    /// Everything the user typed for this character.
+    pub fn fields(&self) -> [&str; 6] {
+        [
+            &self.name,
+            &self.species,
+            &self.age,
+            &self.clothing,
+            &self.colors,
+            &self.features,
+        ]
+    }
+
+    pub fn new(id: u32) -> Self {
+        Character {
+            id,
+            name: format!("Character {id}"),
+            ..Default::default()
+        }
+    }
+
+    /// One visual description, e.g.
+    /// `Mira (red fox, about 8 years old; wearing a green cloak; colors: rust-orange fur)`.
+    pub fn describe(&self) -> String {
+        let who = [self.species.trim(), self.age.trim()]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut parts = vec![];
+        if !who.is_empty() {
+            parts.push(who);
+        }
+        if !self.clothing.trim().is_empty() {
+            parts.push(format!("wearing {}", self.clothing.trim()));
+        }
+        if !self.colors.trim().is_empty() {
+            parts.push(format!("colors: {}", self.colors.trim()));
+        }
+        if !self.features.trim().is_empty() {
+            parts.push(format!("distinctive features: {}", self.features.trim()));
+        }
+
+        let name = self.name.trim();
+        if parts.is_empty() {
+            name.to_string()
+        } else {
+            format!("{name} ({})", parts.join("; "))
+        }
+    }
+}
+
+pub fn next_character_id(bible: &[Character]) -> u32 {
+    bible.iter().map(|c| c.id).max().unwrap_or(0) + 1
+}
+
+/// Prompt sentence describing the characters toggled on for a slot,
+/// in bible order. Empty when nobody appears.
+pub fn cast_line(bible: &[Character], ids: &[u32]) -> String {
+    let cast = bible
+        .iter()
+        .filter(|c| ids.contains(&c.id) && !c.name.trim().is_empty())
+        .map(Character::describe)
+        .collect::<Vec<_>>();
+
+    if cast.is_empty() {
+        return String::new();
+    }
+    format!(
+        "Characters (keep their appearance identical in every image): {}.",
+        cast.join("; ")
+    )
+}
@@ -0,0 +1,187 @@
+use gloo_net::http::{Request, Response};
+use web_sys::{RequestMode, Url};
+
+use crate::backend::RequestTimeout;
+use crate::errors::GenError;
+
+// ----------------------------
+// Worker connection test
+// ----------------------------
+// Probes a Cloudflare Worker URL before a batch does: GET /__version on the
+// Worker's origin, then a POST to the configured URL with an empty body. The
+// POST's JSON and Authorization headers make the browser send a real CORS
+// preflight; the Worker answers an authorized empty body with 400 "prompt is
+// required" without running the model, so the test costs no AI quota. Each
+// probe gives up after PROBE_TIMEOUT_SECS.
+
+pub const PROBE_TIMEOUT_SECS: u32 = 15;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Connection {
+    Ok { version: String },
+    BadUrl(String),
+    Network(String),           // nothing answered, not even to a no-cors request
+    Cors { preflight: bool },  // the server answered but the browser withheld the response
+    NotWorker { status: u16 }, // origin answered, but not with /__version
+    WrongPath { path: String },
+    BadKey { sent: bool },
+    Unexpected(GenError),
+}
+
+impl Connection {
+    pub fn ok(&self) -> bool {
+        matches!(self, Connection::Ok { .. })
+    }
+
+    pub fn summary(&self) -> String {
+        match self {
+            Connection::Ok { version } => format!("Connected ✅ {version}"),
+            Connection::BadUrl(url) => format!("“{url}” is not a valid URL."),
+            Connection::Network(e) => format!("Could not reach the Worker: {e}"),
+            Connection::Cors { preflight: false } => {
+                "The Worker answered, but the browser blocked the response (CORS).".to_string()
+            }
+            Connection::Cors { preflight: true } => {
+                "The Worker answered /__version, but the browser blocked the generate preflight (CORS)."
+                    .to_string()
+            }
+            Connection::NotWorker { status } => {
+                format!("The host answered /__version with HTTP {status}; it does not look like this Worker.")
+            }
+            Connection::WrongPath { path } => format!("The Worker has no endpoint at “{path}” (404)."),
+            Connection::BadKey { sent: true } => "The Worker rejected the API key (401).".to_string(),
+            Connection::BadKey { sent: false } => {
+                "The Worker requires an API key and none is set (401).".to_string()
+            }
+            Connection::Unexpected(e) => format!("Unexpected answer: {e}"),
+        }
+    }
+
+    /// What to change, with this page's origin filled in where it matters.
+    pub fn fix(&self, origin: &str) -> Option<String> {
+        let fix = match self {
+            Connection::Ok { .. } => return None,
+            Connection::BadUrl(_) => {
+                "Paste the full URL, e.g. https://<worker>.<account>.workers.dev/api/generate."
+            }
+            Connection::Network(_) => {
+                "Check the host name for typos, that the Worker is deployed \
+                 (`npx wrangler deploy`) and that you are online."
+            }
+            Connection::Cors { .. } => {
+                return Some(format!(
+                    "Add {origin} to the Worker's ALLOWED_ORIGINS (comma-separated) and redeploy."
+                ))
+            }
+            Connection::NotWorker { .. } => {
+                "Check the Worker name / subdomain in the URL, or redeploy worker/ so /__version exists."
+            }
+            Connection::WrongPath { .. } => "The URL must end with /api/generate.",
+            Connection::BadKey { .. } => {
+                "Paste the Worker's API_KEY secret into App API Key \
+                 (`npx wrangler secret put API_KEY` sets a new one)."
+            }
+            Connection::Unexpected(_) => "Check the Worker logs (`npx wrangler tail`).",
+        };
+        Some(fix.to_string())
+    }
+}
+
+/// This page's origin, as the Worker's allowlist sees it.
+pub fn page_origin() -> String {
+    web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default()
+}
+
+/// A request that fails in cors mode but succeeds in no-cors mode reached a
+/// server that did not allow our origin.
+async fn reachable_without_cors(url: &str) -> bool {
+    let call = RequestTimeout::start(PROBE_TIMEOUT_SECS, None);
+    let r = Request::get(url)
+        .mode(RequestMode::NoCors)
+        .abort_signal(call.signal().as_ref());
+    match r.build() {
+        Ok(req) => req.send().await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Send a probe; one that hangs past the timeout fails as `GenError::Timeout`.
+async fn send_probe(req: Request, call: &RequestTimeout) -> Result<Response, GenError> {
+    call.check(
+        req.send()
+            .await
+            .map_err(|e| GenError::Network(e.to_string())),
+    )
+}
+
+/// Test the Worker behind `url` (its /api/generate URL) with key `token`.
+pub async fn test_worker(url: &str, token: &str) -> Connection {
+    let url = url.trim();
+    let token = token.trim();
+    let Ok(parsed) = Url::new(url) else {
+        return Connection::BadUrl(url.to_string());
+    };
+    let version_url = format!("{}/__version", parsed.origin());
+
+    // 1) GET /__version: a simple request, no preflight
+    let call = RequestTimeout::start(PROBE_TIMEOUT_SECS, None);
+    let Ok(req) = Request::get(&version_url)
+        .abort_signal(call.signal().as_ref())
+        .build()
+    else {
+        return Connection::BadUrl(url.to_string());
+    };
+    let version = match send_probe(req, &call).await {
+        Ok(resp) if resp.ok() => resp.text().await.unwrap_or_default(),
+        Ok(resp) => {
+            return Connection::NotWorker {
+                status: resp.status(),
+            }
+        }
+        Err(e @ GenError::Timeout(_)) => return Connection::Network(e.to_string()),
+        Err(e) => {
+            return if reachable_without_cors(&version_url).await {
+                Connection::Cors { preflight: false }
+            } else {
+                Connection::Network(e.to_string())
+            };
+        }
+    };
+
+    // 2) POST {} to the configured URL: preflight, auth, path
+    let call = RequestTimeout::start(PROBE_TIMEOUT_SECS, None);
+    let mut r = Request::post(url)
+        .header("Content-Type", "application/json")
+        .abort_signal(call.signal().as_ref());
+    if !token.is_empty() {
+        r = r.header("Authorization", &format!("Bearer {token}"));
+    }
+    let Ok(req) = r.body("{}") else {
+        return Connection::BadUrl(url.to_string());
+    };
+    match send_probe(req, &call).await {
+        // 400 "prompt is required" means auth passed and nothing was generated
+        Ok(resp) if resp.ok() || resp.status() == 400 => Connection::Ok { version },
+        Ok(resp) if resp.status() == 401 => Connection::BadKey {
+            sent: !token.is_empty(),
+        },
+        Ok(resp) if resp.status() == 404 || resp.status() == 405 => Connection::WrongPath {
+            path: parsed.pathname(),
+        },
+        Ok(resp) => {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            Connection::Unexpected(GenError::from_status(status, body, None))
+        }
+        Err(e @ GenError::Timeout(_)) => Connection::Network(e.to_string()),
+        Err(e) => {
+            if reachable_without_cors(url).await {
+                Connection::Cors { preflight: true }
+            } else {
+                Connection::Network(e.to_string())
+            }
+        }
+    }
+}
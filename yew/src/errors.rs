@@ -0,0 +1,100 @@
+use std::fmt;
+
+// ----------------------------
+// Generation errors
+// ----------------------------
+// One typed error per failed slot, kept for the error panel instead of being
+// squeezed into the status line.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenError {
+    Network(String), // no (complete) response: offline, DNS, CORS rejection, dropped body
+    Timeout(u32),    // no answer within this many seconds; the request was aborted
+    Http {
+        status: u16,
+        body: String,
+        retry_after_ms: Option<u32>, // from Retry-After, when sent
+    },
+    Auth {
+        status: u16, // 401 missing/wrong API key, 403 origin not allowed
+        body: String,
+    },
+    EmptyBody,      // 200 OK without image bytes
+    Setup(String),  // the server answered but can't make this image: not set up, or the run failed
+    Decode(String), // bytes could not be turned into a loadable image
+    Canvas(String), // 16:9 PNG post-processing failed
+    Cancelled,      // the batch was canceled while this slot was running
+}
+
+impl GenError {
+    /// HTTP failures split into auth problems and everything else.
+    pub fn from_status(status: u16, body: String, retry_after_ms: Option<u32>) -> GenError {
+        match status {
+            401 | 403 => GenError::Auth { status, body },
+            _ => GenError::Http {
+                status,
+                body,
+                retry_after_ms,
+            },
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GenError::Network(_) => "Network",
+            GenError::Timeout(_) => "Timeout",
+            GenError::Http { .. } => "HTTP",
+            GenError::Auth { .. } => "Auth",
+            GenError::EmptyBody => "Empty body",
+            GenError::Setup(_) => "Setup",
+            GenError::Decode(_) => "Decode",
+            GenError::Canvas(_) => "Canvas",
+            GenError::Cancelled => "Canceled",
+        }
+    }
+}
+
+impl fmt::Display for GenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenError::Network(e) => write!(f, "Network error: {e}"),
+            GenError::Timeout(secs) => write!(f, "No answer within {secs} s (request aborted)"),
+            GenError::Http { status, body, .. } => write!(f, "HTTP {status} — {body}"),
+            GenError::Auth { status, body } => {
+                write!(
+                    f,
+                    "HTTP {status} — {body} (check the API key / allowed origins)"
+                )
+            }
+            GenError::EmptyBody => write!(f, "The Worker answered 200 OK without image data"),
+            GenError::Setup(e) => write!(f, "Server could not generate: {e}"),
+            GenError::Decode(e) => write!(f, "Could not decode image: {e}"),
+            GenError::Canvas(e) => write!(f, "16:9 PNG conversion failed: {e}"),
+            GenError::Cancelled => write!(f, "Canceled"),
+        }
+    }
+}
+
+/// A failure recorded for one slot (and candidate) of a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotError {
+    pub slot: u32, // PromptItem id; `key` is its name when it failed
+    pub key: String,
+    pub variant: Option<u32>, // candidate index when generating several
+    pub error: GenError,
+    pub attempts: u32,
+    pub at: f64, // Date.now() when it was given up on
+}
+
+impl SlotError {
+    /// Local wall-clock time, "HH:MM:SS".
+    pub fn time(&self) -> String {
+        let d = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(self.at));
+        format!(
+            "{:02}:{:02}:{:02}",
+            d.get_hours(),
+            d.get_minutes(),
+            d.get_seconds()
+        )
+    }
+}
@@ -0,0 +1,500 @@
+// ----------------------------
+// Prompt linter
+// ----------------------------
+// Flags words that tend to come out as gibberish text in the image (signs,
+// titled books, quoted names, menus, screens) and words the Worker's safety
+// lines forbid (weapons, gore, horror). Every hit carries a suggested rewrite.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    TextBearing,
+    Unsafe,
+}
+
+impl LintKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            LintKind::TextBearing => "Invites text",
+            LintKind::Unsafe => "Against Worker policy",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintIssue {
+    pub kind: LintKind,
+    pub start: usize, // byte range of `found` in the linted text
+    pub end: usize,
+    pub found: String,
+    pub suggestion: String, // replacement for `found`; empty = remove it
+    pub why: &'static str,
+}
+
+impl LintIssue {
+    pub fn describe(&self) -> String {
+        if self.suggestion.is_empty() {
+            format!(
+                "{}: “{}” → remove. {}",
+                self.kind.label(),
+                self.found,
+                self.why
+            )
+        } else {
+            format!(
+                "{}: “{}” → “{}”. {}",
+                self.kind.label(),
+                self.found,
+                self.suggestion,
+                self.why
+            )
+        }
+    }
+}
+
+struct Rule {
+    kind: LintKind,
+    why: &'static str,
+    words: &'static [(&'static str, &'static str)], // lowercase word → rewrite
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        kind: LintKind::TextBearing,
+        why: "Signs and posters get painted with gibberish lettering.",
+        words: &[
+            ("sign", "blank wooden sign"),
+            ("signs", "blank wooden signs"),
+            ("signpost", "plain wooden post"),
+            ("signboard", "blank board"),
+            ("billboard", "blank billboard"),
+            ("poster", "plain colorful poster"),
+            ("posters", "plain colorful posters"),
+            ("banner", "plain fabric banner"),
+            ("banners", "plain fabric banners"),
+            ("storefront", "shop window with a plain awning"),
+            ("storefronts", "shop windows with plain awnings"),
+            ("label", "plain tag"),
+            ("labels", "plain tags"),
+        ],
+    },
+    Rule {
+        kind: LintKind::TextBearing,
+        why: "Printed matter invites titles and lines of fake text.",
+        words: &[
+            ("book", "closed book with a blank cover"),
+            ("books", "closed books with blank covers"),
+            ("letter", "folded blank paper"),
+            ("letters", "folded blank papers"),
+            ("newspaper", "folded blank paper"),
+            ("magazine", "plain folded paper"),
+            ("menu", "plate of food"),
+            ("menus", "plates of food"),
+            ("map", "plain picture map"),
+            ("scroll", "rolled blank scroll"),
+            ("calendar", "plain wall hanging"),
+            ("chalkboard", "blank chalkboard"),
+            ("blackboard", "blank chalkboard"),
+            ("whiteboard", "blank whiteboard"),
+        ],
+    },
+    Rule {
+        kind: LintKind::TextBearing,
+        why: "Screens fill up with fake interface text.",
+        words: &[
+            ("screen", "softly glowing blank screen"),
+            ("screens", "softly glowing blank screens"),
+            ("computer", "computer with a blank glowing screen"),
+            ("laptop", "closed laptop"),
+            ("phone", "phone with a blank screen"),
+            ("tablet", "tablet with a blank screen"),
+            ("tv", "television with a blank screen"),
+            ("television", "television with a blank screen"),
+            ("monitor", "blank monitor"),
+        ],
+    },
+    Rule {
+        kind: LintKind::Unsafe,
+        why: "The Worker forbids weapons.",
+        words: &[
+            ("weapon", "tool"),
+            ("weapons", "tools"),
+            ("sword", "walking stick"),
+            ("swords", "walking sticks"),
+            ("gun", "bubble wand"),
+            ("guns", "bubble wands"),
+            ("rifle", "telescope"),
+            ("pistol", "flashlight"),
+            ("knife", "wooden spoon"),
+            ("knives", "wooden spoons"),
+            ("dagger", "shiny key"),
+            ("axe", "garden rake"),
+            ("spear", "long walking staff"),
+            ("bomb", "balloon"),
+            ("cannon", "big drum"),
+        ],
+    },
+    Rule {
+        kind: LintKind::Unsafe,
+        why: "The Worker forbids gore and violence.",
+        words: &[
+            ("blood", "spilled berry juice"),
+            ("bloody", "messy"),
+            ("gore", "mess"),
+            ("wound", "bandage"),
+            ("wounded", "bandaged"),
+            ("corpse", "sleeping figure"),
+            ("dead", "sleeping"),
+            ("kill", "stop"),
+            ("killing", "stopping"),
+            ("fight", "friendly contest"),
+            ("fighting", "playfully competing"),
+            ("battle", "friendly contest"),
+            ("war", "big disagreement"),
+            ("attack", "surprise visit"),
+            ("violence", "tension"),
+            ("violent", "tense"),
+        ],
+    },
+    Rule {
+        kind: LintKind::Unsafe,
+        why: "The Worker forbids horror imagery.",
+        words: &[
+            ("horror", "mystery"),
+            ("zombie", "sleepy creature"),
+            ("zombies", "sleepy creatures"),
+            ("skeleton", "scarecrow"),
+            ("skull", "round stone"),
+            ("demon", "mischievous sprite"),
+            ("haunted", "mysterious"),
+            ("creepy", "curious"),
+            ("scary", "mysterious"),
+            ("terrifying", "surprising"),
+            ("nightmare", "strange dream"),
+        ],
+    },
+];
+
+/// Words that introduce a name or title ("a bakery called Sweet Dreams").
+const NAMING_WORDS: [&str; 8] = [
+    "called", "named", "titled", "entitled", "labeled", "labelled", "says", "reads",
+];
+/// Things whose name gets painted on them. A naming word only counts right
+/// after one of these; "a fox named Mira" keeps its protagonist.
+const TITLED_NOUNS: &[&str] = &[
+    "shop",
+    "shops",
+    "store",
+    "bakery",
+    "cafe",
+    "café",
+    "restaurant",
+    "inn",
+    "tavern",
+    "hotel",
+    "bookshop",
+    "bookstore",
+    "library",
+    "school",
+    "theater",
+    "theatre",
+    "market",
+    "ship",
+    "boat",
+    "book",
+    "books",
+    "storybook",
+    "newspaper",
+    "magazine",
+    "letter",
+    "map",
+    "menu",
+    "scroll",
+    "sign",
+    "signs",
+    "signpost",
+    "signboard",
+    "billboard",
+    "poster",
+    "banner",
+    "label",
+];
+
+/// Hits shortly after a negation ("no weapons") are instructions, not content.
+const NEGATIONS: [&str; 6] = ["no", "not", "without", "never", "avoid", "nor"];
+/// Text-bearing nouns next to these ("a blank sign") are already made safe.
+const MITIGATIONS: [&str; 6] = [
+    "blank",
+    "plain",
+    "closed",
+    "unreadable",
+    "wordless",
+    "textless",
+];
+
+/// Quoted spans up to this many words read as names or titles, not descriptions.
+const MAX_QUOTED_WORDS: usize = 4;
+
+const NAMES_WHY: &str = "Names and titles get painted into the image as (misspelled) text.";
+
+struct Word<'a> {
+    start: usize,
+    end: usize,
+    text: &'a str,
+}
+
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut out = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                out.push(Word {
+                    start: s,
+                    end: i,
+                    text: &text[s..i],
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn is_open_quote(c: char) -> bool {
+    matches!(c, '"' | '“')
+}
+
+fn is_close_quote(c: char) -> bool {
+    matches!(c, '"' | '”')
+}
+
+/// Short quoted spans, including the quotes.
+fn quoted_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut out = vec![];
+    let mut open: Option<(usize, usize)> = None; // quote start, text start
+    for (i, c) in text.char_indices() {
+        match open {
+            Some((start, inner)) if is_close_quote(c) => {
+                let n = words(&text[inner..i]).len();
+                if n > 0 && n <= MAX_QUOTED_WORDS {
+                    out.push((start, i + c.len_utf8()));
+                }
+                open = None;
+            }
+            None if is_open_quote(c) => open = Some((i, i + c.len_utf8())),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn near(ws: &[Word], i: usize, before: usize, after: usize, set: &[&str]) -> bool {
+    let from = i.saturating_sub(before);
+    let to = (i + after + 1).min(ws.len());
+    (from..to).any(|j| j != i && set.contains(&ws[j].text.to_lowercase().as_str()))
+}
+
+/// "called Sweet Dreams" / "named “Max”": the naming word plus the name.
+fn naming_span(text: &str, ws: &[Word], i: usize, quotes: &[(usize, usize)]) -> Option<usize> {
+    let after = text[ws[i].end..].trim_start();
+    let gap = text.len() - ws[i].end - after.len();
+    if after.starts_with(is_open_quote) {
+        let at = ws[i].end + gap;
+        return quotes.iter().find(|q| q.0 == at).map(|q| q.1);
+    }
+    // A run of capitalized words separated by single spaces.
+    let mut end = None;
+    for w in &ws[i + 1..] {
+        let joined = text[end.unwrap_or(ws[i].end)..w.start]
+            .chars()
+            .all(|c| c == ' ');
+        if !joined || !w.text.starts_with(char::is_uppercase) {
+            break;
+        }
+        end = Some(w.end);
+    }
+    end
+}
+
+/// All issues in `text`, in order, without overlaps.
+pub fn lint(text: &str) -> Vec<LintIssue> {
+    let ws = words(text);
+    let quotes = quoted_spans(text);
+    let mut issues = vec![];
+
+    for (i, w) in ws.iter().enumerate() {
+        let lower = w.text.to_lowercase();
+
+        if NAMING_WORDS.contains(&lower.as_str()) {
+            let titled = i > 0 && TITLED_NOUNS.contains(&ws[i - 1].text.to_lowercase().as_str());
+            if let Some(end) = naming_span(text, &ws, i, &quotes).filter(|_| titled) {
+                issues.push(LintIssue {
+                    kind: LintKind::TextBearing,
+                    start: w.start,
+                    end,
+                    found: text[w.start..end].to_string(),
+                    suggestion: String::new(),
+                    why: NAMES_WHY,
+                });
+            }
+            continue;
+        }
+
+        let hit = RULES.iter().find_map(|r| {
+            r.words
+                .iter()
+                .find(|(word, _)| *word == lower)
+                .map(|(_, rewrite)| (r, *rewrite))
+        });
+        let Some((rule, rewrite)) = hit else {
+            continue;
+        };
+        if near(&ws, i, 3, 0, &NEGATIONS) {
+            continue;
+        }
+        if rule.kind == LintKind::TextBearing && near(&ws, i, 3, 3, &MITIGATIONS) {
+            continue;
+        }
+        issues.push(LintIssue {
+            kind: rule.kind,
+            start: w.start,
+            end: w.end,
+            found: w.text.to_string(),
+            suggestion: rewrite.to_string(),
+            why: rule.why,
+        });
+    }
+
+    // Quoted words on their own: keep the words, lose the quotes.
+    for &(start, end) in &quotes {
+        let found = &text[start..end];
+        let inner = found
+            .trim_start_matches(is_open_quote)
+            .trim_end_matches(is_close_quote);
+        issues.push(LintIssue {
+            kind: LintKind::TextBearing,
+            start,
+            end,
+            found: found.to_string(),
+            suggestion: inner.to_string(),
+            why: "Quoted words get drawn as lettering.",
+        });
+    }
+
+    // Keep the earliest (and for equal starts, the longest) of overlapping hits.
+    issues.sort_by_key(|x| (x.start, std::cmp::Reverse(x.end)));
+    let mut out: Vec<LintIssue> = vec![];
+    for x in issues {
+        if out.last().map(|l| x.start >= l.end).unwrap_or(true) {
+            out.push(x);
+        }
+    }
+    out
+}
+
+fn match_case(found: &str, rewrite: &str) -> String {
+    let mut chars = rewrite.chars();
+    match (found.starts_with(char::is_uppercase), chars.next()) {
+        (true, Some(first)) => first.to_uppercase().chain(chars).collect(),
+        _ => rewrite.to_string(),
+    }
+}
+
+/// `text` with one issue's suggestion applied.
+pub fn apply_fix(text: &str, issue: &LintIssue) -> String {
+    let (mut start, mut end) = (issue.start, issue.end);
+    if text.get(start..end) != Some(issue.found.as_str()) {
+        return text.to_string(); // stale issue: text changed since linting
+    }
+    if issue.suggestion.is_empty() {
+        // Removing a phrase: take one neighbouring space with it.
+        if text[end..].starts_with(' ') {
+            end += 1;
+        } else if text[..start].ends_with(' ') {
+            start -= 1;
+        }
+    }
+    format!(
+        "{}{}{}",
+        &text[..start],
+        match_case(&issue.found, &issue.suggestion),
+        &text[end..]
+    )
+}
+
+/// Issues in `text` that `baseline` doesn't have: what was added to a
+/// prompt the app generated, without flagging the app's own wording.
+pub fn lint_added(text: &str, baseline: &str) -> Vec<LintIssue> {
+    let known = lint(baseline);
+    lint(text)
+        .into_iter()
+        .filter(|i| !known.iter().any(|k| k.found == i.found))
+        .collect()
+}
+
+/// `text` with the suggestions of `issues` (in order, as `lint` returns them) applied.
+pub fn apply_issues(text: &str, issues: &[LintIssue]) -> String {
+    issues
+        .iter()
+        .rev()
+        .fold(text.to_string(), |t, issue| apply_fix(&t, issue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<String> {
+        lint(text).into_iter().map(|i| i.found).collect()
+    }
+
+    #[test]
+    fn names_after_titled_nouns_are_removed() {
+        let text = "A bakery called Sweet Dreams on the corner";
+        assert_eq!(found(text), ["called Sweet Dreams"]);
+        assert_eq!(apply_issues(text, &lint(text)), "A bakery on the corner");
+    }
+
+    #[test]
+    fn character_names_stay() {
+        assert!(lint("Mira reads Max a bedtime story").is_empty());
+        assert!(lint("a fox named Mira explores the woods").is_empty());
+    }
+
+    #[test]
+    fn short_quotes_lose_their_quotes() {
+        let text = "a fox named \u{201c}Mira\u{201d} in the snow";
+        assert_eq!(found(text), ["\u{201c}Mira\u{201d}"]);
+        assert_eq!(
+            apply_issues(text, &lint(text)),
+            "a fox named Mira in the snow"
+        );
+    }
+
+    #[test]
+    fn negated_and_mitigated_words_pass() {
+        assert!(lint("no weapons, a blank sign").is_empty());
+        assert_eq!(found("a knight with a sword"), ["sword"]);
+    }
+
+    #[test]
+    fn rewrites_keep_capitals() {
+        let text = "Sword in hand";
+        assert_eq!(apply_issues(text, &lint(text)), "Walking stick in hand");
+    }
+
+    #[test]
+    fn added_issues_ignore_the_baseline() {
+        let base = "A closed book with a blank cover. A knight.";
+        let edited = format!("{base} He holds a sword.");
+        assert_eq!(
+            lint_added(&edited, base)
+                .into_iter()
+                .map(|i| i.found)
+                .collect::<Vec<_>>(),
+            ["sword"]
+        );
+    }
+}
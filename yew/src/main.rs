@@ -1,4 +1,3 @@
-use gloo_net::http::Request;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -9,12 +8,47 @@ use web_sys::{
 };
 use yew::prelude::*;
 
+mod backend;
+mod batch;
+mod budget;
+mod characters;
+mod connection;
+mod errors;
+mod lint;
+mod project;
+mod settings;
+mod slots;
+mod styles;
+mod worker_prompt;
+use backend::{Backend, BackendKind, ImageBackend, RequestTimeout};
+use batch::{start_batch, BatchCallbacks, BatchHandle};
+use budget::{assemble, Assembled, BudgetCut, Segment, SegmentTag};
+use characters::{cast_line, next_character_id, Character};
+use connection::{page_origin, test_worker, PROBE_TIMEOUT_SECS};
+use errors::{GenError, SlotError};
+use lint::{apply_fix, apply_issues, lint, lint_added, LintIssue};
+use project::{
+    delete_project, export_project_json, insert_project, load_project, load_project_index,
+    load_startup_project, new_project_id, parse_project_file, project_file_stem,
+    save_active_project_id, save_project, save_project_index, Project,
+};
+use settings::{
+    GenSettings, ImageModel, ATTEMPTS_MAX, CONCURRENCY_MAX, FLUX_STEPS_MAX, SDXL_GUIDANCE_MAX,
+    SDXL_GUIDANCE_MIN, SDXL_SIZE_MAX, SDXL_SIZE_MIN, SDXL_STEPS_MAX, TIMEOUT_SECS_MAX,
+    TIMEOUT_SECS_MIN, VARIANTS_MAX,
+};
+use slots::{can_add_slot, insert_position, parse_slot_key, pretty_slot_name, slot_keys, SlotKind};
+use styles::{style_preset, StylePreset, STYLE_PRESETS};
+use worker_prompt::{
+    check_truncation, client_prompt_budget, length_label, worker_coverage, WorkerCoverage,
+};
+
 // ----------------------------
 // LocalStorage helpers
 // ----------------------------
 const LS_WORKER_URL: &str = "ebook_prompt_studio_worker_url";
 const LS_API_KEY: &str = "ebook_prompt_studio_api_key";
-const LS_PREMISE: &str = "ebook_prompt_studio_premise";
+const LS_BACKEND: &str = "ebook_prompt_studio_backend";
 
 fn load_local_storage(key: &str) -> String {
     web_sys::window()
@@ -35,114 +69,504 @@ fn remove_local_storage(key: &str) {
     }
 }
 
+/// Each backend remembers its own URL; the Worker keeps its original key.
+fn backend_url_key(kind: BackendKind) -> String {
+    match kind {
+        BackendKind::Cloudflare => LS_WORKER_URL.to_string(),
+        _ => format!("ebook_prompt_studio_{}_url", kind.id()),
+    }
+}
+
+/// Keys are per backend too: a Worker token is no Automatic1111 "user:pass".
+fn backend_api_key_key(kind: BackendKind) -> String {
+    match kind {
+        BackendKind::Cloudflare => LS_API_KEY.to_string(),
+        _ => format!("ebook_prompt_studio_{}_api_key", kind.id()),
+    }
+}
+
+fn load_backend_url(kind: BackendKind) -> String {
+    let v = load_local_storage(&backend_url_key(kind));
+    if v.trim().is_empty() {
+        kind.default_url().to_string()
+    } else {
+        v
+    }
+}
+
 // ----------------------------
 // Prompt size control (Cloudflare AI limit)
 // ----------------------------
-const MAX_WORKER_PROMPT_CHARS: usize = 2048;
+const MAX_WORKER_PROMPT_CHARS: usize = 2048; // JavaScript .length, i.e. UTF-16 code units
 
 // ----------------------------
 // App Models
 // ----------------------------
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct PromptItem {
-    key: String,      // "cover", "prologue", "ch1"... "credits"
+    kind: SlotKind,
+    #[serde(default)]
+    id: u32, // stable across layout edits (0 until refresh_slots assigns one)
+    key: String,      // "cover", "prologue", "ch1"... "credits" (derived from layout)
     filename: String, // "cover.jpg"...
-    prompt: String,
+    prompt: String,   // what gets sent (generated, or hand-edited)
+    #[serde(default)]
+    edited: bool, // hand-edited; regenerate keeps `prompt` as is
+    #[serde(default)]
+    synopsis: String, // what happens in this slot; empty = generic beat
+    #[serde(default)]
+    characters: Vec<u32>, // Character ids appearing in this slot
+    #[serde(default)]
+    style: Option<String>, // style preset override; None = project style
+    #[serde(default)]
+    seed: Option<u32>, // last seed used, or the pinned seed when locked
+    #[serde(default)]
+    seed_locked: bool, // locked seeds are reused by every batch
+    #[serde(skip)]
+    budget_cuts: Vec<BudgetCut>, // what the budget trimmed from the generated prompt
+    #[serde(skip)]
+    generated: String, // builder output, even when edited; the linter ignores what's in here
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// One generated image for a slot.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Candidate {
+    #[serde(skip)]
+    preview_url: String, // object URL for preview (this session only)
+    #[serde(skip)]
+    download_url: String, // object URL for download (this session only)
+    #[serde(default)]
+    seed: Option<u32>, // seed sent to the Worker; reuse it to reproduce this image
+    #[serde(default)]
+    meta: ImageMeta,
+}
+
+/// What the Worker reported for one image (X-* response headers) plus timing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct ImageMeta {
+    model: Option<String>,     // X-Model
+    steps: Option<u32>,        // X-Steps (FLUX) or X-Num-Steps (SDXL)
+    guidance: Option<f64>,     // X-Guidance (SDXL only)
+    style: Option<String>,     // X-Style
+    prompt_chars: Option<u32>, // X-Prompt-Chars: prompt length after the Worker's suffix and trim
+    elapsed_ms: Option<u32>,   // request start to last response byte
+}
+
+impl ImageMeta {
+    /// "flux-1-schnell • 6 steps • storybook • 1843-unit prompt • 4.2 s"
+    fn summary(&self) -> String {
+        let mut parts = vec![];
+        if let Some(m) = &self.model {
+            parts.push(m.clone());
+        }
+        if let Some(n) = self.steps {
+            parts.push(format!("{n} steps"));
+        }
+        if let Some(g) = self.guidance {
+            parts.push(format!("guidance {g}"));
+        }
+        if let Some(s) = &self.style {
+            parts.push(s.clone());
+        }
+        if let Some(n) = self.prompt_chars {
+            parts.push(format!("{n}-unit prompt"));
+        }
+        if let Some(ms) = self.elapsed_ms {
+            parts.push(format!("{:.1} s", ms as f64 / 1000.0));
+        }
+        parts.join(" • ")
+    }
+}
+
+/// A slot's generated candidates; downloads and exports use the chosen one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RenderedImageFile")]
 struct RenderedImage {
+    slot: u32, // PromptItem id; `key` and the filenames follow that slot
     key: String,
-    preview_filename: String,  // original worker output (jpg)
-    preview_url: String,       // object URL for preview
+    preview_filename: String, // original backend output (jpg from the Worker)
     download_filename: String, // 16:9 png filename
-    download_url: String,      // object URL for download
+    candidates: Vec<Candidate>,
+    chosen: usize, // index into `candidates`
+}
+
+impl RenderedImage {
+    fn chosen(&self) -> Option<&Candidate> {
+        self.candidates.get(self.chosen).or(self.candidates.first())
+    }
+}
+
+/// Stored form of `RenderedImage`; also reads images saved before candidates.
+#[derive(Deserialize)]
+struct RenderedImageFile {
+    #[serde(default)]
+    slot: u32, // 0 in images saved before slot ids; matched by key on load
+    key: String,
+    preview_filename: String,
+    download_filename: String,
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(default)]
+    chosen: usize,
+    #[serde(default)]
+    seed: Option<u32>, // single-image format
 }
 
-#[derive(Serialize)]
-struct GenerateReq<'a> {
-    prompt: &'a str,
-    model: &'a str, // "flux"
-    style: &'a str, // "animated3d"
-    steps: u32,     // flux: max 8
-    seed: Option<u32>,
+impl From<RenderedImageFile> for RenderedImage {
+    fn from(f: RenderedImageFile) -> Self {
+        let candidates = if f.candidates.is_empty() {
+            vec![Candidate {
+                seed: f.seed,
+                ..Candidate::default()
+            }]
+        } else {
+            f.candidates
+        };
+        RenderedImage {
+            slot: f.slot,
+            key: f.key,
+            preview_filename: f.preview_filename,
+            download_filename: f.download_filename,
+            chosen: f.chosen.min(candidates.len() - 1),
+            candidates,
+        }
+    }
 }
 
 // ----------------------------
 // Prompt builder
 // ----------------------------
-fn trim_to_max_prompt(s: String, max_chars: usize) -> String {
-    let t = s.trim().to_string();
-    if t.chars().count() <= max_chars {
-        return t;
-    }
-    // Keep the end of the prompt (style/safety lines are usually at the end)
-    let mut out = String::new();
-    let mut count = 0usize;
-    for ch in t.chars().rev() {
-        if count >= max_chars {
-            break;
+// Story arc used to spread chapter beats over any chapter count.
+const CHAPTER_BEATS: [&str; 6] = [
+    "introduce protagonist doing a simple action that sets the story in motion.",
+    "friendly interaction or small challenge, upbeat tone.",
+    "discovery moment—visual clue, mild suspense without fear.",
+    "obstacle moment—show problem visually, still kid-safe.",
+    "teamwork or learning moment—progress and hope.",
+    "resolution moment—celebration or calm victory.",
+];
+
+fn chapter_beat(n: usize, chapters: usize) -> &'static str {
+    let last = CHAPTER_BEATS.len() - 1;
+    if chapters <= 1 {
+        return CHAPTER_BEATS[0];
+    }
+    // First chapter introduces, last chapter resolves; round in between.
+    let span = chapters - 1;
+    let idx = ((n.saturating_sub(1) * last * 2) + span) / (span * 2);
+    CHAPTER_BEATS[idx.min(last)]
+}
+
+fn as_sentence(s: &str) -> String {
+    if s.ends_with(['.', '!', '?']) {
+        s.to_string()
+    } else {
+        format!("{s}.")
+    }
+}
+
+/// The slot's scene sentence: its synopsis if written, else the generic beat.
+fn slot_scene(slot: &str, synopsis: &str, chapters: usize) -> String {
+    let synopsis = synopsis.trim();
+    match parse_slot_key(slot) {
+        // A written synopsis replaces the generic beat for this slot.
+        Some((SlotKind::Cover, _)) if !synopsis.is_empty() => {
+            format!("Cover art: {}", as_sentence(synopsis))
+        }
+        _ if !synopsis.is_empty() => {
+            format!("{} scene: {}", pretty_slot_name(slot), as_sentence(synopsis))
         }
-        out.push(ch);
-        count += 1;
-    }
-    out.chars().rev().collect::<String>()
-}
-
-fn build_prompt(premise: &str, slot: &str) -> String {
-    let base = format!(
-        "Illustrated eBook scene for: \"{premise}\". \
-         Create a clean, family-friendly, storybook-cinematic image. \
-         Landscape orientation. Compose for a 16:9 wide cinematic frame (safe to crop). \
-         No text, no logos, no watermark."
-    );
-
-    let crop_safe =
-        "Keep key subjects centered with generous margins; avoid important details near edges (crop-safe 16:9).";
-
-    let slot_specific = match slot {
-        "cover" => "Cover art: iconic moment that communicates the theme, clear focal subject, inviting warm lighting.",
-        "prologue" => "Prologue scene: establish setting and mood, gentle intrigue, readable composition.",
-        "ch1" => "Chapter 1 scene: introduce protagonist doing a simple action that sets the story in motion.",
-        "ch2" => "Chapter 2 scene: friendly interaction or small challenge, upbeat tone.",
-        "ch3" => "Chapter 3 scene: discovery moment—visual clue, mild suspense without fear.",
-        "ch4" => "Chapter 4 scene: obstacle moment—show problem visually, still kid-safe.",
-        "ch5" => "Chapter 5 scene: teamwork or learning moment—progress and hope.",
-        "ch6" => "Chapter 6 scene: resolution moment—celebration or calm victory.",
-        "epilogue" => "Epilogue scene: peaceful wrap-up, cozy closing image.",
-        "credits" => "Credits background: simple pleasing backdrop with space for overlay later (but generate with NO TEXT).",
-        _ => "Scene: cohesive with the story.",
+        Some((SlotKind::Cover, _)) => "Cover art: iconic moment that communicates the theme, clear focal subject, inviting warm lighting.".to_string(),
+        Some((SlotKind::FrontMatter, _)) => "Front matter illustration: decorative scene that sets the story's tone, simple and uncluttered.".to_string(),
+        Some((SlotKind::Prologue, _)) => "Prologue scene: establish setting and mood, gentle intrigue, readable composition.".to_string(),
+        Some((SlotKind::Chapter, n)) => format!("Chapter {n} scene: {}", chapter_beat(n, chapters)),
+        Some((SlotKind::Interlude, _)) => "Interlude scene: quiet in-between moment, atmospheric pause that bridges the chapters.".to_string(),
+        Some((SlotKind::Epilogue, _)) => "Epilogue scene: peaceful wrap-up, cozy closing image.".to_string(),
+        Some((SlotKind::BackMatter, _)) => "Back matter illustration: calm decorative scene echoing the story's world, simple and uncluttered.".to_string(),
+        Some((SlotKind::Credits, _)) => "Credits background: simple pleasing backdrop with space for overlay later (but generate with NO TEXT).".to_string(),
+        None => "Scene: cohesive with the story.".to_string(),
+    }
+}
+
+/// Assemble a slot prompt from tagged segments, fitted to `budget` UTF-16 units.
+/// In compact mode, lines the Worker appends anyway are left out so their
+/// budget goes to the scene and characters instead.
+fn build_prompt(
+    premise: &str,
+    slot: &str,
+    synopsis: &str,
+    cast: &str,
+    style: &StylePreset,
+    chapters: usize,
+    compact: bool,
+) -> Assembled {
+    let skip = if compact {
+        worker_coverage(style)
+    } else {
+        WorkerCoverage::default()
     };
 
-    // Safe “Pixar-adjacent” vibe without naming a specific studio.
-    let animated_3d = "High-quality 3D animated family film look, soft global illumination, warm cinematic lighting, \
-                      detailed materials, subtle subsurface scattering, clean shapes, crisp focus on subject, \
-                      gentle depth of field, ultra clean render.";
+    let mut segments = vec![Segment::new(
+        SegmentTag::Premise,
+        format!("Illustrated eBook scene for: \"{premise}\"."),
+    )];
+    if !(skip.safety && skip.no_text) {
+        segments.push(Segment::new(
+            SegmentTag::Safety,
+            "Create a clean, family-friendly, storybook-cinematic image. \
+             No text, no logos, no watermark. Natural proportions.",
+        ));
+    }
+    segments.push(Segment::new(
+        SegmentTag::Composition,
+        if skip.crop_safe {
+            "Landscape orientation. Compose for a 16:9 wide cinematic frame (safe to crop)."
+        } else {
+            "Landscape orientation. Compose for a 16:9 wide cinematic frame (safe to crop). \
+             Keep key subjects centered with generous margins; avoid important details near edges (crop-safe 16:9)."
+        },
+    ));
+    segments.push(Segment::new(
+        SegmentTag::Scene,
+        slot_scene(slot, synopsis, chapters),
+    ));
+    // Same descriptions in every slot keep characters from drifting between images.
+    segments.push(Segment::new(SegmentTag::Characters, cast));
+    if !skip.style {
+        segments.push(Segment::new(SegmentTag::Style, style.fragment));
+    }
+
+    // Leave room for the lines the Worker appends, so it never cuts our front.
+    assemble(segments, client_prompt_budget(style.worker_style))
+}
+
+// ----------------------------
+// Slots: layout <-> PromptItems
+// ----------------------------
+fn new_slot(kind: SlotKind) -> PromptItem {
+    PromptItem {
+        kind,
+        id: 0,
+        key: String::new(),
+        filename: String::new(),
+        prompt: String::new(),
+        edited: false,
+        synopsis: String::new(),
+        characters: vec![],
+        style: None,
+        seed: None,
+        seed_locked: false,
+        budget_cuts: vec![],
+        generated: String::new(),
+    }
+}
 
-    let full = format!(
-        "{base} {crop_safe} {slot_specific} {animated_3d} Natural proportions."
-    );
+// ----------------------------
+// Seeds
+// ----------------------------
+fn random_seed() -> u32 {
+    // Stay within i32 so every backend accepts it as a plain integer.
+    (js_sys::Math::random() * i32::MAX as f64) as u32
+}
 
-    // Ensure we do not exceed the worker prompt limit
-    trim_to_max_prompt(full, MAX_WORKER_PROMPT_CHARS)
+/// Seed for candidate `variant` of a slot: the slot seed, incremented.
+fn variant_seed(seed: Option<u32>, variant: u32) -> Option<u32> {
+    seed.map(|s| ((s as u64 + variant as u64) % i32::MAX as u64) as u32)
 }
 
-fn pretty_slot_name(key: &str) -> &str {
-    match key {
-        "cover" => "Cover",
-        "prologue" => "Prologue",
-        "ch1" => "Chapter 1",
-        "ch2" => "Chapter 2",
-        "ch3" => "Chapter 3",
-        "ch4" => "Chapter 4",
-        "ch5" => "Chapter 5",
-        "ch6" => "Chapter 6",
-        "epilogue" => "Epilogue",
-        "credits" => "Credits",
-        _ => key,
+/// Give every unlocked slot a fresh seed (and any locked slot without one).
+fn randomize_unlocked_seeds<'a>(slots: impl IntoIterator<Item = &'a mut PromptItem>) {
+    for item in slots {
+        if !item.seed_locked || item.seed.is_none() {
+            item.seed = Some(random_seed());
+        }
     }
 }
 
+/// The slot's own style override, else the project style.
+fn slot_style(item: &PromptItem, settings: &GenSettings) -> &'static StylePreset {
+    style_preset(item.style.as_deref().unwrap_or(&settings.style))
+}
+
+fn next_slot_id(slots: &[PromptItem]) -> u32 {
+    slots.iter().map(|s| s.id).max().unwrap_or(0) + 1
+}
+
+/// Original (as delivered) filename for a slot's image of type `ext`.
+fn original_filename(item: &PromptItem, ext: &str) -> String {
+    match ext {
+        "jpg" => item.filename.clone(),
+        _ => format!("{}-original.{ext}", item.key),
+    }
+}
+
+/// `images` renamed after their slots' current keys and in slot order;
+/// images whose slot is gone are dropped.
+fn rekey_images(images: &[RenderedImage], slots: &[PromptItem]) -> Vec<RenderedImage> {
+    let mut out = images
+        .iter()
+        .filter_map(|img| {
+            let item = slots.iter().find(|s| s.id == img.slot)?;
+            let mut img = img.clone();
+            if img.key != item.key {
+                let ext = img
+                    .preview_filename
+                    .rsplit('.')
+                    .next()
+                    .unwrap_or("jpg")
+                    .to_string();
+                img.preview_filename = original_filename(item, &ext);
+                img.download_filename = format!("{}.png", item.key);
+                img.key = item.key.clone();
+            }
+            Some(img)
+        })
+        .collect::<Vec<_>>();
+    out.sort_by_key(|i| slots.iter().position(|s| s.id == i.slot));
+    out
+}
+
+/// Re-derive keys/filenames after a layout change and rebuild each prompt.
+/// Hand-edited prompts are left alone until reset. Saved images follow
+/// their slot to its new key.
+fn refresh_slots(p: &mut Project) {
+    for i in 0..p.slots.len() {
+        if p.slots[i].id == 0 {
+            p.slots[i].id = next_slot_id(&p.slots);
+        }
+    }
+    // Images saved before slot ids: the slots still carry the keys they were saved under
+    for img in p.images.iter_mut().filter(|i| i.slot == 0) {
+        if let Some(item) = p.slots.iter().find(|s| s.key == img.key) {
+            img.slot = item.id;
+        }
+    }
+    let layout = p.slots.iter().map(|s| s.kind).collect::<Vec<_>>();
+    let chapters = layout.iter().filter(|k| **k == SlotKind::Chapter).count();
+    for (item, key) in p.slots.iter_mut().zip(slot_keys(&layout)) {
+        item.filename = format!("{key}.jpg");
+        let cast = cast_line(&p.characters, &item.characters);
+        let style = slot_style(item, &p.settings);
+        let compact = p.settings.compact_for_worker;
+        let generated = build_prompt(
+            &p.premise,
+            &key,
+            &item.synopsis,
+            &cast,
+            style,
+            chapters,
+            compact,
+        );
+        item.budget_cuts = generated.cuts;
+        if item.edited && item.prompt == generated.text {
+            item.edited = false;
+        }
+        if !item.edited {
+            item.prompt = generated.text.clone();
+        }
+        item.generated = generated.text;
+        item.key = key;
+    }
+    p.images = rekey_images(&p.images, &p.slots);
+}
+
+/// `errors` renamed after their slots' current keys; failures of removed slots are dropped.
+fn rekey_errors(errors: &[SlotError], slots: &[PromptItem]) -> Vec<SlotError> {
+    errors
+        .iter()
+        .filter_map(|e| {
+            let item = slots.iter().find(|s| s.id == e.slot)?;
+            Some(SlotError {
+                key: item.key.clone(),
+                ..e.clone()
+            })
+        })
+        .collect()
+}
+
+/// Apply an edit to the active project and persist it (prompts untouched).
+fn update_project(project: &UseStateHandle<Project>, edit: impl FnOnce(&mut Project)) {
+    let mut next = (**project).clone();
+    edit(&mut next);
+    save_project(&next);
+    project.set(next);
+}
+
+/// Apply an edit to slots or characters, then re-key, rebuild prompts and persist.
+fn update_slots(project: &UseStateHandle<Project>, edit: impl FnOnce(&mut Project)) {
+    let mut next = (**project).clone();
+    edit(&mut next);
+    if next.slots.is_empty() {
+        return;
+    }
+    refresh_slots(&mut next);
+    save_project(&next);
+    project.set(next);
+}
+
+fn add_slot(items: &mut Vec<PromptItem>, kind: SlotKind) {
+    let layout = items.iter().map(|p| p.kind).collect::<Vec<_>>();
+    if can_add_slot(&layout, kind) {
+        items.insert(insert_position(&layout, kind), new_slot(kind));
+    }
+}
+
+/// Grow or shrink the chapter run to exactly `n` chapters.
+fn set_chapter_count(items: &mut Vec<PromptItem>, n: usize) {
+    let mut have = items.iter().filter(|p| p.kind == SlotKind::Chapter).count();
+    while have < n {
+        add_slot(items, SlotKind::Chapter);
+        have += 1;
+    }
+    while have > n {
+        if let Some(i) = items.iter().rposition(|p| p.kind == SlotKind::Chapter) {
+            items.remove(i);
+        }
+        have -= 1;
+    }
+}
+
+/// Make `p` the active project and show its saved gallery. Failures and the
+/// slot selection belong to the project that was open, so they go.
+fn open_project(
+    project: &UseStateHandle<Project>,
+    images: &UseStateHandle<Vec<RenderedImage>>,
+    slot_errors: &UseStateHandle<Vec<SlotError>>,
+    selected: &UseStateHandle<Vec<String>>,
+    p: Project,
+) {
+    save_active_project_id(&p.id);
+    images.set(p.images.clone());
+    slot_errors.set(vec![]);
+    selected.set(vec![]);
+    project.set(p);
+}
+
+/// `base` with each fresh image replacing that slot's old one (or added),
+/// ordered like the slots.
+fn merge_images(
+    base: &[RenderedImage],
+    fresh: Vec<RenderedImage>,
+    slots: &[PromptItem],
+) -> Vec<RenderedImage> {
+    let mut out = base.to_vec();
+    for img in fresh {
+        match out.iter_mut().find(|i| i.slot == img.slot) {
+            Some(old) => *old = img,
+            None => out.push(img),
+        }
+    }
+    let rank = |slot: u32| {
+        slots
+            .iter()
+            .position(|s| s.id == slot)
+            .unwrap_or(usize::MAX)
+    };
+    out.sort_by_key(|i| rank(i.slot));
+    out
+}
+
 // ----------------------------
 // Helpers: bytes -> Blob -> object URL
 // ----------------------------
@@ -160,6 +584,26 @@ fn bytes_to_object_url(bytes: &[u8], mime: &str) -> Result<String, String> {
     Url::create_object_url_with_blob(&blob).map_err(|_| "Failed to create object URL".to_string())
 }
 
+/// Trigger a browser download of `text` through a temporary `<a download>`.
+fn download_text(filename: &str, mime: &str, text: &str) -> Result<(), String> {
+    let url = bytes_to_object_url(text.as_bytes(), mime)?;
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("No document")?;
+
+    let a: web_sys::HtmlElement = document
+        .create_element("a")
+        .map_err(|_| "create_element a failed")?
+        .dyn_into()
+        .map_err(|_| "dyn_into HtmlElement failed")?;
+    a.set_attribute("href", &url)
+        .map_err(|_| "set href failed")?;
+    a.set_attribute("download", filename)
+        .map_err(|_| "set download failed")?;
+    a.click();
+    Ok(())
+}
+
 // ----------------------------
 // 16:9 crop+resize -> PNG object URL
 // ----------------------------
@@ -167,7 +611,15 @@ async fn make_16x9_png_object_url(
     preview_url: &str,
     out_w: u32,
     out_h: u32,
-) -> Result<String, String> {
+) -> Result<String, GenError> {
+    let img = load_image(preview_url).await.map_err(GenError::Decode)?;
+    draw_16x9_png(&img, out_w, out_h)
+        .await
+        .map_err(GenError::Canvas)
+}
+
+/// Load `url` into an `<img>` and wait until it has decoded.
+async fn load_image(url: &str) -> Result<HtmlImageElement, String> {
     let document = web_sys::window()
         .and_then(|w| w.document())
         .ok_or("No document")?;
@@ -198,7 +650,7 @@ async fn make_16x9_png_object_url(
 
     img.set_onload(Some(onload.as_ref().unchecked_ref()));
     img.set_onerror(Some(onerror.as_ref().unchecked_ref()));
-    img.set_src(preview_url);
+    img.set_src(url);
 
     onload.forget();
     onerror.forget();
@@ -214,12 +666,15 @@ async fn make_16x9_png_object_url(
     if iw < 2.0 || ih < 2.0 {
         return Err("Invalid natural image size".to_string());
     }
+    Ok(img)
+}
 
-    // Compute 16:9 crop rect
+/// Centered 16:9 crop rect (x, y, w, h) of a `iw`×`ih` image.
+fn crop_16x9(iw: f64, ih: f64) -> (f64, f64, f64, f64) {
     let target_ratio = 16.0 / 9.0;
     let src_ratio = iw / ih;
 
-    let (sx, sy, sw, sh) = if src_ratio > target_ratio {
+    if src_ratio > target_ratio {
         // too wide -> crop width
         let new_w = ih * target_ratio;
         let x = (iw - new_w) / 2.0;
@@ -229,17 +684,23 @@ async fn make_16x9_png_object_url(
         let new_h = iw / target_ratio;
         let y = (ih - new_h) / 2.0;
         (0.0, y, iw, new_h)
-    };
+    }
+}
+
+/// A detached `w`×`h` canvas and its 2D context.
+fn canvas_2d(w: u32, h: u32) -> Result<(HtmlCanvasElement, CanvasRenderingContext2d), String> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("No document")?;
 
-    // Canvas
     let canvas: HtmlCanvasElement = document
         .create_element("canvas")
         .map_err(|_| "create_element canvas failed")?
         .dyn_into()
         .map_err(|_| "dyn_into HtmlCanvasElement failed")?;
 
-    canvas.set_width(out_w);
-    canvas.set_height(out_h);
+    canvas.set_width(w);
+    canvas.set_height(h);
 
     let ctx: CanvasRenderingContext2d = canvas
         .get_context("2d")
@@ -247,6 +708,19 @@ async fn make_16x9_png_object_url(
         .ok_or("2d context missing")?
         .dyn_into()
         .map_err(|_| "dyn_into CanvasRenderingContext2d failed")?;
+    Ok((canvas, ctx))
+}
+
+/// Center-crop `img` to 16:9, scale to `out_w`×`out_h` and return a PNG object URL.
+async fn draw_16x9_png(img: &HtmlImageElement, out_w: u32, out_h: u32) -> Result<String, String> {
+    let iw = img.natural_width() as f64;
+    let ih = img.natural_height() as f64;
+
+    // Compute 16:9 crop rect
+    let (sx, sy, sw, sh) = crop_16x9(iw, ih);
+
+    // Canvas
+    let (canvas, ctx) = canvas_2d(out_w, out_h)?;
 
     // IMPORTANT:
     // Some web-sys builds don't expose the 9-arg drawImage overload.
@@ -261,7 +735,7 @@ async fn make_16x9_png_object_url(
         .map_err(|_| "set_transform failed")?;
 
     // Draw full image; transform makes it behave like a cropped draw
-    ctx.draw_image_with_html_image_element(&img, 0.0, 0.0)
+    ctx.draw_image_with_html_image_element(img, 0.0, 0.0)
         .map_err(|_| "draw_image failed")?;
 
     // Reset transform
@@ -297,175 +771,631 @@ async fn make_16x9_png_object_url(
     Url::create_object_url_with_blob(&blob).map_err(|_| "Failed to create PNG object URL".to_string())
 }
 
+// ----------------------------
+// Lint panel
+// ----------------------------
+/// Linter `issues` found in `text`, each with an inline fix that hands the
+/// rewritten text to `apply`.
+fn lint_panel(text: &str, issues: Vec<LintIssue>, busy: bool, apply: Callback<String>) -> Html {
+    if issues.is_empty() {
+        return html! {};
+    }
+    let fixed_all = apply_issues(text, &issues);
+    html! {
+        <ul style="color: #7c2d12; margin: 4px 0; padding-left: 18px; font-size: 13px;">
+            { for issues.iter().map(|issue| {
+                let fixed = apply_fix(text, issue);
+                let apply = apply.clone();
+                html!{
+                    <li>
+                        {issue.describe()}{" "}
+                        <button disabled={busy} onclick={Callback::from(move |_| apply.emit(fixed.clone()))}>{"Apply"}</button>
+                    </li>
+                }
+            }) }
+            if issues.len() > 1 {
+                <li style="list-style: none; margin-top: 4px;">
+                    <button disabled={busy} onclick={Callback::from(move |_| apply.emit(fixed_all.clone()))}>{"Apply all suggestions"}</button>
+                </li>
+            }
+        </ul>
+    }
+}
+
+/// What the user added to a hand-edited prompt; generated text is linted at its source.
+fn edited_prompt_issues(item: &PromptItem) -> Vec<LintIssue> {
+    if item.edited {
+        lint_added(&item.prompt, &item.generated)
+    } else {
+        vec![]
+    }
+}
+
+/// Linter findings in what the user wrote: premise, characters, and the given
+/// slots' synopses and hand-edited prompts. The builder's own text is not linted.
+fn lint_count<'a>(p: &Project, slots: impl IntoIterator<Item = &'a PromptItem>) -> usize {
+    let cast = p
+        .characters
+        .iter()
+        .flat_map(|c| c.fields())
+        .map(|f| lint(f).len())
+        .sum::<usize>();
+    let slots = slots
+        .into_iter()
+        .map(|s| lint(&s.synopsis).len() + edited_prompt_issues(s).len())
+        .sum::<usize>();
+    lint(&p.premise).len() + cast + slots
+}
+
 // ----------------------------
 // Yew App
 // ----------------------------
+/// What "Test connection" found, ready to show.
+#[derive(Clone, Debug, PartialEq)]
+struct ConnectionResult {
+    ok: bool,
+    summary: String,
+    fix: Option<String>, // what to change when it failed
+}
+
 #[function_component(App)]
 fn app() -> Html {
     // Load initial values from LocalStorage (paste once, remember)
-    let premise = use_state(|| load_local_storage(LS_PREMISE));
+    let project = use_state(load_startup_project);
+    let projects = use_state(load_project_index);
+
+    let backend_kind =
+        use_state(|| BackendKind::from_id(&load_local_storage(LS_BACKEND)).unwrap_or_default());
+    let worker_url = {
+        let kind = *backend_kind;
+        use_state(move || load_backend_url(kind)) // URL of the selected backend
+    };
 
-    let worker_url = use_state(|| {
-        let v = load_local_storage(LS_WORKER_URL);
-        if v.trim().is_empty() {
-            "https://ebook-image-forge.mikegyver.workers.dev/api/generate".to_string()
-        } else {
-            v
-        }
-    });
+    let api_key = {
+        let kind = *backend_kind;
+        use_state(move || load_local_storage(&backend_api_key_key(kind))) // key of the selected backend
+    };
+
+    let images = {
+        let saved = project.images.clone();
+        use_state(move || saved)
+    };
+    let busy = use_state(|| false);
+    let batch = use_mut_ref(|| None::<BatchHandle>); // running batch, for cancel/pause
+    let paused = use_state(|| false);
+    let selected = use_state(Vec::<String>::new); // slot keys ticked for a partial batch
+    let slot_errors = use_state(Vec::<SlotError>::new); // failures of the latest runs, per slot
+    let connection = use_state(|| None::<ConnectionResult>); // last "Test connection" outcome
+    let status = use_state(String::new);
+    let add_kind = use_state(|| SlotKind::Chapter);
+
+    // Keep the active project's image metadata in step with the gallery.
+    {
+        let project = project.clone();
+        use_effect_with((*images).clone(), move |imgs| {
+            if project.images != *imgs {
+                update_project(&project, |p| p.images = imgs.clone());
+            }
+        });
+    }
+
+    // Slot keys follow the layout, so a reorder, removal or new chapter count
+    // re-keys slots: gallery images and failures follow their slot, and a
+    // ticked key may now name a different slot.
+    {
+        let selected = selected.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let slots = project.slots.clone();
+        let keys = slots
+            .iter()
+            .map(|s| (s.id, s.key.clone()))
+            .collect::<Vec<_>>();
+        use_effect_with(keys, move |_| {
+            let rekeyed = rekey_images(&images, &slots);
+            if rekeyed != *images {
+                images.set(rekeyed);
+            }
+            let rekeyed = rekey_errors(&slot_errors, &slots);
+            if rekeyed != *slot_errors {
+                slot_errors.set(rekeyed);
+            }
+            if !selected.is_empty() {
+                selected.set(vec![]);
+            }
+        });
+    }
+
+    let on_switch_project = {
+        let project = project.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let selected = selected.clone();
+        Callback::from(move |e: Event| {
+            let id = e
+                .target_unchecked_into::<web_sys::HtmlSelectElement>()
+                .value();
+            if let Some(p) = load_project(&id) {
+                open_project(&project, &images, &slot_errors, &selected, p);
+            }
+        })
+    };
 
-    let api_key = use_state(|| load_local_storage(LS_API_KEY));
-
-    let prompts = {
-        let premise = premise.clone();
-        use_state(move || {
-            let keys = [
-                "cover", "prologue", "ch1", "ch2", "ch3", "ch4", "ch5", "ch6", "epilogue", "credits",
-            ];
-            keys.iter()
-                .map(|k| PromptItem {
-                    key: k.to_string(),
-                    filename: format!("{k}.jpg"),
-                    prompt: build_prompt(&premise, k),
+    let on_new_project = {
+        let project = project.clone();
+        let projects = projects.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let selected = selected.clone();
+        Callback::from(move |_| {
+            let mut index = (*projects).clone();
+            let p = Project::new(
+                new_project_id(&index),
+                format!("Untitled book {}", index.len() + 1),
+            );
+            insert_project(&mut index, &p);
+            projects.set(index);
+            open_project(&project, &images, &slot_errors, &selected, p);
+        })
+    };
+
+    let on_duplicate_project = {
+        let project = project.clone();
+        let projects = projects.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let selected = selected.clone();
+        Callback::from(move |_| {
+            let mut index = (*projects).clone();
+            let mut copy = (*project).clone();
+            copy.id = new_project_id(&index);
+            copy.name = format!("{} (copy)", copy.name);
+            insert_project(&mut index, &copy);
+            projects.set(index);
+            open_project(&project, &images, &slot_errors, &selected, copy);
+        })
+    };
+
+    let on_delete_project = {
+        let project = project.clone();
+        let projects = projects.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let selected = selected.clone();
+        Callback::from(move |_| {
+            let confirmed = web_sys::window()
+                .and_then(|w| {
+                    w.confirm_with_message(&format!(
+                        "Delete “{}”? This cannot be undone.",
+                        project.name
+                    ))
+                    .ok()
                 })
-                .collect::<Vec<_>>()
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+
+            let mut index = (*projects).clone();
+            delete_project(&project.id);
+            index.retain(|m| m.id != project.id);
+
+            let next = match index.first().and_then(|m| load_project(&m.id)) {
+                Some(p) => p,
+                None => {
+                    let p = Project::new(new_project_id(&index), "Untitled book 1".to_string());
+                    insert_project(&mut index, &p);
+                    p
+                }
+            };
+            save_project_index(&index);
+            projects.set(index);
+            open_project(&project, &images, &slot_errors, &selected, next);
         })
     };
 
-    let images = use_state(|| Vec::<RenderedImage>::new());
-    let busy = use_state(|| false);
-    let status = use_state(|| String::new());
+    let on_export_project = {
+        let project = project.clone();
+        let status = status.clone();
+        Callback::from(move |_| {
+            let result = export_project_json(&project).and_then(|json| {
+                let filename = format!("{}.ebook.json", project_file_stem(&project.name));
+                download_text(&filename, "application/json", &json)
+            });
+            if let Err(e) = result {
+                status.set(e);
+            }
+        })
+    };
+
+    let on_import_project = {
+        let project = project.clone();
+        let projects = projects.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let selected = selected.clone();
+        let status = status.clone();
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<web_sys::HtmlInputElement>();
+            let Some(file) = input.files().and_then(|f| f.get(0)) else {
+                return;
+            };
+            // Allow importing the same file again later
+            input.set_value("");
+
+            let project = project.clone();
+            let projects = projects.clone();
+            let images = images.clone();
+            let slot_errors = slot_errors.clone();
+            let selected = selected.clone();
+            let status = status.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let text = match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                    Ok(v) => v.as_string().unwrap_or_default(),
+                    Err(_) => {
+                        status.set(format!("Could not read {}", file.name()));
+                        return;
+                    }
+                };
+
+                match parse_project_file(&text) {
+                    Ok(mut p) => {
+                        let mut index = (*projects).clone();
+                        p.id = new_project_id(&index);
+                        insert_project(&mut index, &p);
+                        projects.set(index);
+                        status.set(format!("Imported “{}” ✅", p.name));
+                        open_project(&project, &images, &slot_errors, &selected, p);
+                    }
+                    Err(e) => status.set(e),
+                }
+            });
+        })
+    };
+
+    let on_rename_project = {
+        let project = project.clone();
+        let projects = projects.clone();
+        Callback::from(move |e: InputEvent| {
+            let v = e
+                .target_unchecked_into::<web_sys::HtmlInputElement>()
+                .value();
+            let mut index = (*projects).clone();
+            if let Some(m) = index.iter_mut().find(|m| m.id == project.id) {
+                m.name = v.clone();
+            }
+            save_project_index(&index);
+            projects.set(index);
+            update_project(&project, |p| p.name = v);
+        })
+    };
 
     let regen_prompts = {
-        let premise = premise.clone();
-        let prompts = prompts.clone();
+        let project = project.clone();
+        let status = status.clone();
         Callback::from(move |_| {
-            let prem = (*premise).clone();
-            let keys = [
-                "cover", "prologue", "ch1", "ch2", "ch3", "ch4", "ch5", "ch6", "epilogue", "credits",
-            ];
-            let next = keys
-                .iter()
-                .map(|k| PromptItem {
-                    key: k.to_string(),
-                    filename: format!("{k}.jpg"),
-                    prompt: build_prompt(&prem, k),
-                })
-                .collect::<Vec<_>>();
-            prompts.set(next);
+            // Count on the refreshed project; `project` still holds the old one.
+            let mut kept = 0;
+            update_project(&project, |p| {
+                refresh_slots(p);
+                kept = p.slots.iter().filter(|s| s.edited).count();
+            });
+            if kept > 0 {
+                status.set(format!(
+                    "Prompts regenerated. Kept {kept} hand-edited slot(s) — use “Reset to generated” to replace them."
+                ));
+            }
+        })
+    };
+
+    let on_add_slot = {
+        let project = project.clone();
+        let add_kind = add_kind.clone();
+        Callback::from(move |_| {
+            let kind = *add_kind;
+            update_slots(&project, |p| add_slot(&mut p.slots, kind));
+        })
+    };
+
+    let on_chapter_count = {
+        let project = project.clone();
+        Callback::from(move |e: Event| {
+            let v = e
+                .target_unchecked_into::<web_sys::HtmlInputElement>()
+                .value();
+            if let Ok(n) = v.trim().parse::<usize>() {
+                update_slots(&project, |p| {
+                    set_chapter_count(&mut p.slots, n.clamp(1, 48))
+                });
+            }
+        })
+    };
+
+    let on_add_character = {
+        let project = project.clone();
+        Callback::from(move |_| {
+            update_slots(&project, |p| {
+                let c = Character::new(next_character_id(&p.characters));
+                // New characters start out in every slot; untick where they don't appear.
+                for item in p.slots.iter_mut() {
+                    item.characters.push(c.id);
+                }
+                p.characters.push(c);
+            });
         })
     };
 
+    let on_randomize_seeds = {
+        let project = project.clone();
+        Callback::from(move |_| {
+            update_project(&project, |p| randomize_unlocked_seeds(&mut p.slots))
+        })
+    };
+
+    let backend = Backend::new(*backend_kind, &worker_url, &api_key);
+    let caps = backend.capabilities();
+    let mut settings_errors = project.settings.validate();
+    if !caps.models.contains(&project.settings.model) {
+        settings_errors.push(format!(
+            "{} uses SDXL-style settings — pick SDXL as the model.",
+            backend_kind.label()
+        ));
+    }
+    let chapter_count = project
+        .slots
+        .iter()
+        .filter(|s| s.kind == SlotKind::Chapter)
+        .count();
+    let layout = project.slots.iter().map(|s| s.kind).collect::<Vec<_>>();
+
     let clear_saved_key = {
         let api_key = api_key.clone();
+        let backend_kind = backend_kind.clone();
         Callback::from(move |_| {
-            remove_local_storage(LS_API_KEY);
+            remove_local_storage(&backend_api_key_key(*backend_kind));
             api_key.set(String::new());
         })
     };
 
-    let on_generate_all = {
-        let prompts = prompts.clone();
-        let images = images.clone();
+    let on_backend = {
+        let project = project.clone();
+        let connection = connection.clone();
+        let backend_kind = backend_kind.clone();
         let worker_url = worker_url.clone();
         let api_key = api_key.clone();
+        Callback::from(move |e: Event| {
+            let v = e
+                .target_unchecked_into::<web_sys::HtmlSelectElement>()
+                .value();
+            if let Some(kind) = BackendKind::from_id(&v) {
+                save_local_storage(LS_BACKEND, kind.id());
+                backend_kind.set(kind);
+                connection.set(None);
+                worker_url.set(load_backend_url(kind));
+                api_key.set(load_local_storage(&backend_api_key_key(kind)));
+                // Self-hosted backends only take SDXL-style settings.
+                let models = Backend::new(kind, "", "").capabilities().models;
+                if !models.contains(&project.settings.model) {
+                    update_project(&project, |p| p.settings.model = models[0]);
+                }
+            }
+        })
+    };
+
+    let on_test_connection = {
+        let backend = backend.clone();
+        let worker_url = worker_url.clone();
+        let api_key = api_key.clone();
+        let connection = connection.clone();
+        Callback::from(move |_| {
+            let backend = backend.clone();
+            let (url, key) = ((*worker_url).clone(), (*api_key).clone());
+            let connection = connection.clone();
+            connection.set(Some(ConnectionResult {
+                ok: true,
+                summary: "Testing connection…".to_string(),
+                fix: None,
+            }));
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = match &backend {
+                    Backend::Cloudflare(_) => {
+                        let c = test_worker(&url, &key).await;
+                        ConnectionResult {
+                            ok: c.ok(),
+                            summary: c.summary(),
+                            fix: c.fix(&page_origin()),
+                        }
+                    }
+                    _ => {
+                        let call = RequestTimeout::start(PROBE_TIMEOUT_SECS, None);
+                        match call.check(backend.health(call.signal().as_ref()).await) {
+                            Ok(about) => ConnectionResult {
+                                ok: true,
+                                summary: format!("Connected ✅ {about}"),
+                                fix: None,
+                            },
+                            Err(e) => ConnectionResult {
+                                ok: false,
+                                summary: e.to_string(),
+                                fix: Some(backend.capabilities().setup.to_string()),
+                            },
+                        }
+                    }
+                };
+                connection.set(Some(result));
+            });
+        })
+    };
+
+    // Generate the given slot keys, or the whole book (clearing the gallery) for None.
+    let run_slots = {
+        let project = project.clone();
+        let images = images.clone();
+        let slot_errors = slot_errors.clone();
+        let backend = backend.clone();
+        let settings_errors = settings_errors.clone();
         let busy = busy.clone();
         let status = status.clone();
+        let batch = batch.clone();
+        let paused = paused.clone();
 
-        Callback::from(move |_| {
+        Callback::from(move |keys: Option<Vec<String>>| {
             if *busy {
                 return;
             }
+            if let Some(e) = settings_errors.first() {
+                status.set(format!("Fix generation settings first: {e}"));
+                return;
+            }
+            let wanted =
+                |item: &PromptItem| keys.as_ref().map(|k| k.contains(&item.key)).unwrap_or(true);
+            if !project.slots.iter().any(wanted) {
+                status.set("Select at least one slot to generate.".to_string());
+                return;
+            }
+            let findings = lint_count(&project, project.slots.iter().filter(|s| wanted(s)));
+            if findings > 0 {
+                let go_on = web_sys::window()
+                    .and_then(|w| {
+                        w.confirm_with_message(&format!(
+                            "The linter found {findings} issue(s) that tend to cause garbled text or break Worker policy. Generate anyway?"
+                        ))
+                        .ok()
+                    })
+                    .unwrap_or(false);
+                if !go_on {
+                    status.set("Batch not started — review the linter suggestions.".to_string());
+                    return;
+                }
+            }
 
+            // A partial run keeps every other slot's image where it is.
+            let base = if keys.is_some() {
+                (*images).clone()
+            } else {
+                vec![]
+            };
+            // Earlier failures of the slots being rerun are replaced by this run's.
+            let base_errors = slot_errors
+                .iter()
+                .filter(|e| keys.as_ref().map(|k| !k.contains(&e.key)).unwrap_or(false))
+                .cloned()
+                .collect::<Vec<_>>();
             busy.set(true);
-            images.set(vec![]);
+            images.set(base.clone());
+            slot_errors.set(base_errors.clone());
             status.set("Generating images…".to_string());
 
-            let prompts_list = (*prompts).clone();
-            let url = (*worker_url).clone();
-            let token = (*api_key).clone();
+            // Decide every seed up front so the project records what each slot used.
+            let mut next = (*project).clone();
+            randomize_unlocked_seeds(next.slots.iter_mut().filter(|s| wanted(s)));
+            let prompts_list = next
+                .slots
+                .iter()
+                .filter(|s| wanted(s))
+                .cloned()
+                .collect::<Vec<_>>();
+            let slots = next.slots.clone();
+            update_project(&project, |p| *p = next);
+
             let images_setter = images.clone();
-            let busy_setter = busy.clone();
+            let errors_setter = slot_errors.clone();
             let status_setter = status.clone();
+            let busy_setter = busy.clone();
+            let done_status = status.clone();
+            let done_batch = batch.clone();
+            let done_paused = paused.clone();
+            let handle = start_batch(
+                prompts_list,
+                project.settings.clone(),
+                backend.clone(),
+                BatchCallbacks {
+                    on_images: Callback::from(move |fresh| {
+                        images_setter.set(merge_images(&base, fresh, &slots))
+                    }),
+                    on_errors: Callback::from(move |fresh: Vec<SlotError>| {
+                        errors_setter.set(base_errors.iter().cloned().chain(fresh).collect())
+                    }),
+                    on_status: Callback::from(move |s| status_setter.set(s)),
+                    on_done: Callback::from(move |s| {
+                        done_status.set(s);
+                        busy_setter.set(false);
+                        done_paused.set(false);
+                        *done_batch.borrow_mut() = None;
+                    }),
+                },
+            );
+            if handle.is_running() {
+                *batch.borrow_mut() = Some(handle);
+            }
+        })
+    };
 
-            wasm_bindgen_futures::spawn_local(async move {
-                let mut out: Vec<RenderedImage> = vec![];
-
-                for (idx, item) in prompts_list.iter().enumerate() {
-                    status_setter.set(format!(
-                        "Generating {} ({}/{})…",
-                        pretty_slot_name(&item.key),
-                        idx + 1,
-                        prompts_list.len()
-                    ));
-
-                    let req = GenerateReq {
-                        prompt: &item.prompt,
-                        model: "flux",
-                        style: "animated3d",
-                        steps: 8,
-                        seed: None,
-                    };
-
-                    let mut r = Request::post(&url).header("Content-Type", "application/json");
-                    if !token.trim().is_empty() {
-                        r = r.header("Authorization", &format!("Bearer {}", token.trim()));
-                    }
-
-                    let resp = match r.json(&req).unwrap().send().await {
-                        Ok(v) => v,
-                        Err(_) => continue,
-                    };
-
-                    if !resp.ok() {
-                        // Read text error if present; helps debug without "CORS" confusion
-                        let msg = resp.text().await.unwrap_or_else(|_| "Request failed".into());
-                        status_setter.set(format!(
-                            "{} failed: HTTP {} — {}",
-                            pretty_slot_name(&item.key),
-                            resp.status(),
-                            msg
-                        ));
-                        continue;
-                    }
+    let on_generate_all = run_slots.reform(|_: MouseEvent| None);
 
-                    let bytes = match resp.binary().await {
-                        Ok(b) => b,
-                        Err(_) => continue,
-                    };
-
-                    // Preview URL (JPEG)
-                    let preview_url = match bytes_to_object_url(&bytes, "image/jpeg") {
-                        Ok(u) => u,
-                        Err(_) => continue,
-                    };
+    let on_generate_selected = {
+        let selected = selected.clone();
+        run_slots.reform(move |_: MouseEvent| Some(selected.iter().cloned().collect()))
+    };
 
-                    // 16:9 PNG download (1600x900)
-                    let png_url = match make_16x9_png_object_url(&preview_url, 1600, 900).await {
-                        Ok(u) => u,
-                        Err(_) => preview_url.clone(), // fallback
-                    };
+    let selected_count = project
+        .slots
+        .iter()
+        .filter(|s| selected.contains(&s.key))
+        .count();
 
-                    out.push(RenderedImage {
-                        key: item.key.clone(),
-                        preview_filename: item.filename.clone(),
-                        preview_url,
-                        download_filename: format!("{}.png", item.key),
-                        download_url: png_url,
-                    });
+    let on_cancel_batch = {
+        let batch = batch.clone();
+        Callback::from(move |_| {
+            if let Some(handle) = batch.borrow().as_ref() {
+                handle.cancel();
+            }
+        })
+    };
 
-                    images_setter.set(out.clone());
-                }
+    let on_clear_errors = {
+        let slot_errors = slot_errors.clone();
+        Callback::from(move |_| slot_errors.set(vec![]))
+    };
 
-                status_setter.set("Done ✅".to_string());
-                busy_setter.set(false);
-            });
+    let on_toggle_pause = {
+        let batch = batch.clone();
+        let paused = paused.clone();
+        Callback::from(move |_| {
+            if let Some(handle) = batch.borrow().as_ref() {
+                handle.set_paused(!*paused);
+                paused.set(!*paused);
+            }
         })
     };
 
     html! {
         <div style="font-family: system-ui; max-width: 1100px; margin: 0 auto; padding: 16px;">
-            <h1>{"eBook Prompt Studio → Cloudflare AI (FLUX) → Images"}</h1>
+            <h1>{format!("eBook Prompt Studio → {} ({}) → Images", backend_kind.label(), project.settings.model.label())}</h1>
+
+            <div style="display:flex; gap: 8px; align-items: center; flex-wrap: wrap; margin-bottom: 12px;">
+                <label>{"Project"}</label>
+                <select onchange={on_switch_project} disabled={*busy}>
+                    { for projects.iter().map(|m| html!{
+                        <option value={m.id.clone()} selected={m.id == project.id}>{m.name.clone()}</option>
+                    }) }
+                </select>
+                <input
+                    style="width: 220px;"
+                    value={project.name.clone()}
+                    placeholder="Project name"
+                    oninput={on_rename_project}
+                    disabled={*busy}
+                />
+                <button onclick={on_new_project} disabled={*busy}>{"New"}</button>
+                <button onclick={on_duplicate_project} disabled={*busy}>{"Duplicate"}</button>
+                <button onclick={on_delete_project} disabled={*busy}>{"Delete"}</button>
+                <button onclick={on_export_project} disabled={*busy}>{"Export JSON"}</button>
+                <label style="display:inline-flex; gap: 6px; align-items: center;">
+                    {"Import JSON"}
+                    <input type="file" accept=".json,application/json" onchange={on_import_project} disabled={*busy} />
+                </label>
+            </div>
 
             if !(*status).is_empty() {
                 <p style="opacity:0.85;">{(*status).clone()}</p>
@@ -476,58 +1406,223 @@ fn app() -> Html {
                     <label>{"eBook premise"}</label>
                     <textarea
                         style="width: 100%; height: 110px;"
-                        value={(*premise).clone()}
+                        value={project.premise.clone()}
                         oninput={{
-                            let premise = premise.clone();
+                            let project = project.clone();
                             Callback::from(move |e: InputEvent| {
                                 let v = e.target_unchecked_into::<web_sys::HtmlTextAreaElement>().value();
-                                save_local_storage(LS_PREMISE, &v);
-                                premise.set(v);
+                                update_project(&project, |p| p.premise = v);
                             })
                         }}
                     />
+                    { lint_panel(&project.premise, lint(&project.premise), *busy, {
+                        let project = project.clone();
+                        Callback::from(move |v: String| update_slots(&project, |p| p.premise = v))
+                    }) }
                     <div style="display:flex; gap: 8px; margin-top: 8px;">
                         <button onclick={regen_prompts.clone()} disabled={*busy}>{"Regenerate prompts"}</button>
-                        <button onclick={on_generate_all.clone()} disabled={*busy}>{"Generate images (batch)"}</button>
+                        <button onclick={on_generate_all.clone()} disabled={*busy || !settings_errors.is_empty()}>{"Generate images (batch)"}</button>
+                        <button onclick={on_generate_selected} disabled={*busy || selected_count == 0 || !settings_errors.is_empty()}>
+                            {format!("Generate selected ({selected_count})")}
+                        </button>
+                        if *busy {
+                            <button onclick={on_toggle_pause}>{ if *paused { "Resume" } else { "Pause" } }</button>
+                            <button onclick={on_cancel_batch}>{"Cancel"}</button>
+                        }
                     </div>
+                    {{
+                        let findings = lint_count(&project, &project.slots);
+                        html!{
+                            if findings > 0 {
+                                <p style="color: #7c2d12; margin: 6px 0 0;">
+                                    {format!("Linter: {findings} suggestion(s) in the premise, characters, synopses and edited prompts — review them before generating.")}
+                                </p>
+                            }
+                        }
+                    }}
 
                     <p style="opacity:0.75; margin-top: 10px;">
-                        {format!("Note: prompts are auto-trimmed to {} chars to match your Worker / Cloudflare AI limits.", MAX_WORKER_PROMPT_CHARS)}
+                        {format!("Note: prompts are fitted by priority to leave room for the Worker's own lines within its {}-unit limit (JavaScript .length: emoji count as 2).", MAX_WORKER_PROMPT_CHARS)}
                     </p>
                 </div>
 
                 <div>
-                    <label>{"Worker URL"}</label>
-                    <input
-                        style="width: 100%;"
-                        value={(*worker_url).clone()}
-                        oninput={{
-                            let worker_url = worker_url.clone();
-                            Callback::from(move |e: InputEvent| {
-                                let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
-                                save_local_storage(LS_WORKER_URL, &v);
-                                worker_url.set(v);
-                            })
-                        }}
-                    />
+                    <label>{"Image backend"}</label>
+                    <select style="display:block; margin-bottom: 8px;" onchange={on_backend} disabled={*busy}>
+                        { for BackendKind::ALL.into_iter().map(|k| html!{
+                            <option value={k.id()} selected={k == *backend_kind}>{k.label()}</option>
+                        }) }
+                    </select>
+                    if *backend_kind != BackendKind::Placeholder {
+                        <label>{ if *backend_kind == BackendKind::Cloudflare { "Worker URL" } else { "Server URL" } }</label>
+                        <input
+                            style="width: 100%;"
+                            value={(*worker_url).clone()}
+                            oninput={{
+                                let worker_url = worker_url.clone();
+                                let backend_kind = backend_kind.clone();
+                                let connection = connection.clone();
+                                Callback::from(move |e: InputEvent| {
+                                    let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                    save_local_storage(&backend_url_key(*backend_kind), &v);
+                                    worker_url.set(v);
+                                    connection.set(None);
+                                })
+                            }}
+                        />
+                    }
+                    <p style="opacity:0.75; font-size: 13px; margin: 4px 0;">{caps.setup}</p>
                     <label style="display:block; margin-top: 8px;">{"App API Key (optional)"}</label>
                     <input
                         type="password"
                         style="width: 100%;"
                         value={(*api_key).clone()}
-                        placeholder="Bearer token used by your Worker (not OpenAI)"
+                        placeholder={match *backend_kind {
+                            BackendKind::Cloudflare => "Bearer token used by your Worker (not OpenAI)",
+                            BackendKind::Automatic1111 => "user:pass when started with --api-auth",
+                            BackendKind::ComfyUi => "Not used by ComfyUI",
+                            BackendKind::Placeholder => "Not used by placeholders",
+                        }}
                         oninput={{
                             let api_key = api_key.clone();
+                            let backend_kind = backend_kind.clone();
                             Callback::from(move |e: InputEvent| {
                                 let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
-                                save_local_storage(LS_API_KEY, &v);
+                                save_local_storage(&backend_api_key_key(*backend_kind), &v);
                                 api_key.set(v);
                             })
                         }}
                     />
                     <div style="display:flex; gap: 8px; margin-top: 8px;">
                         <button onclick={clear_saved_key} disabled={*busy}>{"Clear saved key"}</button>
+                        <button onclick={on_test_connection} disabled={*busy}>{"Test connection"}</button>
                     </div>
+                    if let Some(c) = &*connection {
+                        <div style={format!("margin-top: 8px; padding: 6px 8px; border-radius: 6px; background: {};", if c.ok { "#ecfdf5" } else { "#fef2f2" })}>
+                            <div>{c.summary.clone()}</div>
+                            if let Some(fix) = &c.fix {
+                                <div style="font-size: 13px; margin-top: 4px;"><b>{"Fix: "}</b>{fix.clone()}</div>
+                            }
+                        </div>
+                    }
+
+                    <label style="display:block; margin-top: 8px;">{"Model"}</label>
+                    <select
+                        disabled={*busy}
+                        onchange={{
+                            let project = project.clone();
+                            Callback::from(move |e: Event| {
+                                let v = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                                if let Some(m) = ImageModel::from_id(&v) {
+                                    update_project(&project, |p| p.settings.model = m);
+                                }
+                            })
+                        }}
+                    >
+                        { for ImageModel::ALL.into_iter().filter(|m| caps.models.contains(m)).map(|m| html!{
+                            <option value={m.id()} selected={m == project.settings.model}>{m.label()}</option>
+                        }) }
+                    </select>
+                    <label style="display:block; margin-top: 8px;">{"Style"}</label>
+                    <select
+                        disabled={*busy}
+                        onchange={{
+                            let project = project.clone();
+                            Callback::from(move |e: Event| {
+                                let v = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                                update_slots(&project, |p| p.settings.style = v);
+                            })
+                        }}
+                    >
+                        { for STYLE_PRESETS.iter().map(|s| html!{
+                            <option value={s.id} selected={s.id == style_preset(&project.settings.style).id}>{s.label}</option>
+                        }) }
+                    </select>
+                    <label style="display:block; margin-top: 8px;" title="Leave out the crop-safe, no-text, safety and style lines the Worker appends itself">
+                        <input
+                            type="checkbox"
+                            checked={project.settings.compact_for_worker}
+                            disabled={*busy}
+                            onchange={{
+                                let project = project.clone();
+                                Callback::from(move |e: Event| {
+                                    let on = e.target_unchecked_into::<web_sys::HtmlInputElement>().checked();
+                                    update_slots(&project, |p| p.settings.compact_for_worker = on);
+                                })
+                            }}
+                        />
+                        {" Compact for Worker (more room for scene text)"}
+                    </label>
+                    {{
+                        let number = |label: &'static str, value: String, min: String, max: String, step: &'static str, set: fn(&mut GenSettings, f64)| {
+                            let project = project.clone();
+                            html!{
+                                <label style="display:inline-flex; gap: 4px; align-items: center; margin: 6px 10px 0 0;">
+                                    {label}
+                                    <input
+                                        type="number"
+                                        style="width: 80px;"
+                                        min={min}
+                                        max={max}
+                                        step={step}
+                                        value={value}
+                                        disabled={*busy}
+                                        oninput={Callback::from(move |e: InputEvent| {
+                                            let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                            if let Ok(n) = v.trim().parse::<f64>() {
+                                                update_project(&project, |p| set(&mut p.settings, n));
+                                            }
+                                        })}
+                                    />
+                                </label>
+                            }
+                        };
+                        let st = &project.settings;
+                        let model_fields = match st.model {
+                            ImageModel::Flux => html!{
+                                <div>
+                                    { number("Steps", st.flux_steps.to_string(), "1".into(), FLUX_STEPS_MAX.to_string(), "1", |s, n| s.flux_steps = n as u32) }
+                                </div>
+                            },
+                            ImageModel::Sdxl => html!{
+                                <div>
+                                    { number("Width", st.width.to_string(), SDXL_SIZE_MIN.to_string(), SDXL_SIZE_MAX.to_string(), "8", |s, n| s.width = n as u32) }
+                                    { number("Height", st.height.to_string(), SDXL_SIZE_MIN.to_string(), SDXL_SIZE_MAX.to_string(), "8", |s, n| s.height = n as u32) }
+                                    { number("Steps", st.num_steps.to_string(), "1".into(), SDXL_STEPS_MAX.to_string(), "1", |s, n| s.num_steps = n as u32) }
+                                    { number("Guidance", st.guidance.to_string(), SDXL_GUIDANCE_MIN.to_string(), SDXL_GUIDANCE_MAX.to_string(), "0.5", |s, n| s.guidance = n as f32) }
+                                    <label style="display:block; margin-top: 6px;">{"Negative prompt (optional)"}</label>
+                                    <input
+                                        style="width: 100%;"
+                                        value={st.negative_prompt.clone()}
+                                        placeholder="Added before the Worker's built-in no-text negatives"
+                                        disabled={*busy}
+                                        oninput={{
+                                            let project = project.clone();
+                                            Callback::from(move |e: InputEvent| {
+                                                let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                                update_project(&project, |p| p.settings.negative_prompt = v);
+                                            })
+                                        }}
+                                    />
+                                </div>
+                            },
+                        };
+                        html!{
+                            <>
+                                { model_fields }
+                                <div>
+                                    { number("Parallel requests", st.concurrency.to_string(), "1".into(), CONCURRENCY_MAX.to_string(), "1", |s, n| s.concurrency = n as u32) }
+                                    { number("Attempts per image", st.max_attempts.to_string(), "1".into(), ATTEMPTS_MAX.to_string(), "1", |s, n| s.max_attempts = n as u32) }
+                                    { number("Candidates per slot", st.variants.to_string(), "1".into(), VARIANTS_MAX.to_string(), "1", |s, n| s.variants = n as u32) }
+                                    { number("Request timeout (s)", st.timeout_secs.to_string(), TIMEOUT_SECS_MIN.to_string(), TIMEOUT_SECS_MAX.to_string(), "1", |s, n| s.timeout_secs = n as u32) }
+                                </div>
+                            </>
+                        }
+                    }}
+                    { for settings_errors.iter().map(|e| html!{
+                        <p style="color: #b91c1c; margin: 4px 0;">{e.clone()}</p>
+                    }) }
+
                     <p style="opacity:0.8; margin-top: 10px;">
                         {"Download links are 16:9 PNGs (post-processed). Preview is the original JPEG."}
                     </p>
@@ -536,16 +1631,354 @@ fn app() -> Html {
 
             <hr />
 
+            <h2>{"Character bible"}</h2>
+            <p style="opacity:0.75; margin-top: 0;">
+                {"Descriptions are inserted into every slot the character is ticked for, so they look the same from cover to epilogue."}
+            </p>
+            <div style="display: grid; grid-template-columns: 1fr 1fr; gap: 10px;">
+                { for project.characters.iter().enumerate().map(|(ci, c)| {
+                    let field = |label: &'static str, value: &str, placeholder: &'static str, set: fn(&mut Character, String)| {
+                        let issues = lint_panel(value, lint(value), *busy, {
+                            let project = project.clone();
+                            Callback::from(move |v: String| update_slots(&project, |p| set(&mut p.characters[ci], v)))
+                        });
+                        let project = project.clone();
+                        html!{
+                            <label style="display:block; margin-top: 4px;">
+                                {label}
+                                <input
+                                    style="width: 100%;"
+                                    value={value.to_string()}
+                                    placeholder={placeholder}
+                                    disabled={*busy}
+                                    oninput={Callback::from(move |e: InputEvent| {
+                                        let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                        update_slots(&project, |p| set(&mut p.characters[ci], v));
+                                    })}
+                                />
+                                { issues }
+                            </label>
+                        }
+                    };
+                    let on_remove = {
+                        let project = project.clone();
+                        let id = c.id;
+                        Callback::from(move |_| {
+                            update_slots(&project, |p| {
+                                p.characters.retain(|c| c.id != id);
+                                for item in p.slots.iter_mut() {
+                                    item.characters.retain(|c| *c != id);
+                                }
+                            });
+                        })
+                    };
+                    html!{
+                        <div style="border: 1px solid #ddd; border-radius: 10px; padding: 10px;">
+                            <div style="display:flex; justify-content: space-between; gap: 10px;">
+                                <b>{c.name.clone()}</b>
+                                <button onclick={on_remove} disabled={*busy}>{"✕"}</button>
+                            </div>
+                            { field("Name", &c.name, "Mira", |c, v| c.name = v) }
+                            { field("Species", &c.species, "red fox", |c, v| c.species = v) }
+                            { field("Age", &c.age, "about 8 years old", |c, v| c.age = v) }
+                            { field("Clothing", &c.clothing, "green hooded cloak, brown boots", |c, v| c.clothing = v) }
+                            { field("Colors", &c.colors, "rust-orange fur, cream chest", |c, v| c.colors = v) }
+                            { field("Distinctive features", &c.features, "round glasses, white-tipped tail", |c, v| c.features = v) }
+                        </div>
+                    }
+                }) }
+            </div>
+            <div style="margin-top: 8px;">
+                <button onclick={on_add_character} disabled={*busy}>{"Add character"}</button>
+            </div>
+
+            <hr />
+
             <h2>{"Prompts"}</h2>
+            <div style="display:flex; gap: 8px; align-items: center; flex-wrap: wrap; margin-bottom: 10px;">
+                <label>{"Chapters"}</label>
+                <input
+                    type="number"
+                    min="1"
+                    max="48"
+                    style="width: 70px;"
+                    value={chapter_count.to_string()}
+                    onchange={on_chapter_count}
+                    disabled={*busy}
+                />
+                <select
+                    onchange={{
+                        let add_kind = add_kind.clone();
+                        Callback::from(move |e: Event| {
+                            let v = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                            if let Some(k) = SlotKind::ALL.into_iter().find(|k| k.prefix() == v) {
+                                add_kind.set(k);
+                            }
+                        })
+                    }}
+                >
+                    { for SlotKind::ALL.into_iter().map(|k| html!{
+                        <option value={k.prefix()} selected={k == *add_kind}>{k.label()}</option>
+                    }) }
+                </select>
+                <button onclick={on_add_slot} disabled={*busy || !can_add_slot(&layout, *add_kind)}>{"Add slot"}</button>
+                <button onclick={on_randomize_seeds} disabled={*busy}>{"Randomize unlocked seeds"}</button>
+            </div>
             <div style="display: grid; grid-template-columns: 1fr; gap: 10px;">
-                { for (*prompts).iter().map(|p| {
+                { for project.slots.iter().enumerate().map(|(idx, p)| {
                     let title = format!("{} • {}", pretty_slot_name(&p.key), p.filename);
+                    let count = project.slots.len();
+                    let move_by = |delta: isize| {
+                        let project = project.clone();
+                        Callback::from(move |_| {
+                            let to = idx as isize + delta;
+                            if to >= 0 && (to as usize) < count {
+                                update_slots(&project, |p| p.slots.swap(idx, to as usize));
+                            }
+                        })
+                    };
+                    let on_remove = {
+                        let project = project.clone();
+                        Callback::from(move |_| {
+                            update_slots(&project, |p| {
+                                p.slots.remove(idx);
+                            });
+                        })
+                    };
+                    let is_selected = selected.contains(&p.key);
+                    let on_select = {
+                        let selected = selected.clone();
+                        let key = p.key.clone();
+                        Callback::from(move |_| {
+                            let mut next = (*selected).clone();
+                            if next.contains(&key) {
+                                next.retain(|k| *k != key);
+                            } else {
+                                next.push(key.clone());
+                            }
+                            selected.set(next);
+                        })
+                    };
+                    let on_generate_one = {
+                        let key = p.key.clone();
+                        run_slots.reform(move |_: MouseEvent| Some(vec![key.clone()]))
+                    };
                     html!{
                         <div style="border: 1px solid #ddd; border-radius: 10px; padding: 10px;">
                             <div style="display:flex; justify-content: space-between; gap: 10px;">
-                                <b>{title}</b>
+                                <label style="display:flex; gap: 6px; align-items: center;">
+                                    <input type="checkbox" checked={is_selected} onchange={on_select} disabled={*busy} />
+                                    <b>{title}</b>
+                                </label>
+                                <div style="display:flex; gap: 6px;">
+                                    <button onclick={on_generate_one} disabled={*busy || !settings_errors.is_empty()}>{"Generate"}</button>
+                                    <button onclick={move_by(-1)} disabled={*busy || idx == 0}>{"↑"}</button>
+                                    <button onclick={move_by(1)} disabled={*busy || idx + 1 == count}>{"↓"}</button>
+                                    <button onclick={on_remove} disabled={*busy || count == 1}>{"✕"}</button>
+                                </div>
+                            </div>
+                            if !project.characters.is_empty() {
+                                <div style="display:flex; gap: 12px; flex-wrap: wrap; margin-top: 6px;">
+                                    { for project.characters.iter().map(|c| {
+                                        let id = c.id;
+                                        let on_toggle = {
+                                            let project = project.clone();
+                                            Callback::from(move |_| {
+                                                update_slots(&project, |p| {
+                                                    let cast = &mut p.slots[idx].characters;
+                                                    if cast.contains(&id) {
+                                                        cast.retain(|c| *c != id);
+                                                    } else {
+                                                        cast.push(id);
+                                                    }
+                                                });
+                                            })
+                                        };
+                                        html!{
+                                            <label>
+                                                <input
+                                                    type="checkbox"
+                                                    checked={p.characters.contains(&id)}
+                                                    onchange={on_toggle}
+                                                    disabled={*busy}
+                                                />
+                                                {format!(" {}", c.name)}
+                                            </label>
+                                        }
+                                    }) }
+                                </div>
+                            }
+                            <label style="display:block; margin-top: 6px;">
+                                {"Style "}
+                                <select
+                                    disabled={*busy}
+                                    onchange={{
+                                        let project = project.clone();
+                                        Callback::from(move |e: Event| {
+                                            let v = e.target_unchecked_into::<web_sys::HtmlSelectElement>().value();
+                                            update_slots(&project, |p| {
+                                                p.slots[idx].style = (!v.is_empty()).then_some(v);
+                                            });
+                                        })
+                                    }}
+                                >
+                                    <option value="" selected={p.style.is_none()}>
+                                        {format!("Project default ({})", style_preset(&project.settings.style).label)}
+                                    </option>
+                                    { for STYLE_PRESETS.iter().map(|s| html!{
+                                        <option value={s.id} selected={p.style.as_deref() == Some(s.id)}>{s.label}</option>
+                                    }) }
+                                </select>
+                            </label>
+                            <div style="display:flex; gap: 8px; align-items: center; margin-top: 6px;">
+                                <label>{"Seed"}</label>
+                                <input
+                                    type="number"
+                                    min="0"
+                                    style="width: 130px;"
+                                    value={p.seed.map(|s| s.to_string()).unwrap_or_default()}
+                                    placeholder="random"
+                                    disabled={*busy}
+                                    oninput={{
+                                        let project = project.clone();
+                                        Callback::from(move |e: InputEvent| {
+                                            let v = e.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+                                            let seed = v.trim().parse::<u32>().ok();
+                                            // Typing a seed pins it; clearing the field unpins.
+                                            update_project(&project, |p| {
+                                                p.slots[idx].seed = seed;
+                                                p.slots[idx].seed_locked = seed.is_some();
+                                            });
+                                        })
+                                    }}
+                                />
+                                <button
+                                    disabled={*busy}
+                                    onclick={{
+                                        let project = project.clone();
+                                        Callback::from(move |_| {
+                                            update_project(&project, |p| {
+                                                let item = &mut p.slots[idx];
+                                                item.seed_locked = !item.seed_locked;
+                                                if item.seed_locked && item.seed.is_none() {
+                                                    item.seed = Some(random_seed());
+                                                }
+                                            });
+                                        })
+                                    }}
+                                >{ if p.seed_locked { "🔒 Locked" } else { "🔓 Unlocked" } }</button>
+                            </div>
+                            <label style="display:block; margin-top: 6px;">{"Scene synopsis (optional)"}</label>
+                            <textarea
+                                style="width: 100%; height: 48px;"
+                                value={p.synopsis.clone()}
+                                placeholder="What happens in this scene? Leave empty for the generic beat."
+                                disabled={*busy}
+                                oninput={{
+                                    let project = project.clone();
+                                    Callback::from(move |e: InputEvent| {
+                                        let v = e.target_unchecked_into::<web_sys::HtmlTextAreaElement>().value();
+                                        update_slots(&project, |p| p.slots[idx].synopsis = v);
+                                    })
+                                }}
+                            />
+                            { lint_panel(&p.synopsis, lint(&p.synopsis), *busy, {
+                                let project = project.clone();
+                                Callback::from(move |v: String| update_slots(&project, |p| p.slots[idx].synopsis = v))
+                            }) }
+                            <div style="display:flex; justify-content: space-between; align-items: center; gap: 10px; margin-top: 6px;">
+                                <label>
+                                    {"Prompt"}
+                                    if p.edited {
+                                        <span style="margin-left: 8px; color: #b45309; font-weight: 600;">{"● modified"}</span>
+                                    }
+                                </label>
+                                if p.edited {
+                                    <button
+                                        disabled={*busy}
+                                        onclick={{
+                                            let project = project.clone();
+                                            Callback::from(move |_| {
+                                                update_slots(&project, |p| p.slots[idx].edited = false);
+                                            })
+                                        }}
+                                    >{"Reset to generated"}</button>
+                                }
                             </div>
-                            <textarea style="width: 100%; height: 90px;" value={p.prompt.clone()} readonly=true />
+                            <textarea
+                                style="width: 100%; height: 90px;"
+                                value={p.prompt.clone()}
+                                disabled={*busy}
+                                oninput={{
+                                    let project = project.clone();
+                                    Callback::from(move |e: InputEvent| {
+                                        let v = e.target_unchecked_into::<web_sys::HtmlTextAreaElement>().value();
+                                        update_slots(&project, |p| {
+                                            p.slots[idx].prompt = v;
+                                            p.slots[idx].edited = true;
+                                        });
+                                    })
+                                }}
+                            />
+                            { lint_panel(&p.prompt, edited_prompt_issues(p), *busy, {
+                                let project = project.clone();
+                                Callback::from(move |v: String| {
+                                    update_slots(&project, |p| {
+                                        p.slots[idx].prompt = v;
+                                        p.slots[idx].edited = true;
+                                    });
+                                })
+                            }) }
+                            {{
+                                let st = &project.settings;
+                                let check = check_truncation(
+                                    &p.prompt,
+                                    &project.premise,
+                                    &slot_scene(&p.key, &p.synopsis, chapter_count),
+                                    slot_style(p, st).worker_style,
+                                    st.model,
+                                    &st.negative_prompt,
+                                );
+                                let w = &check.worker;
+                                let lost = match (check.premise_cut, check.scene_cut) {
+                                    (true, true) => Some("premise and scene text"),
+                                    (true, false) => Some("premise"),
+                                    (false, true) => Some("scene text"),
+                                    (false, false) => None,
+                                };
+                                html!{
+                                    <>
+                                        if !p.edited && !p.budget_cuts.is_empty() {
+                                            <ul style="color: #b45309; margin: 4px 0; padding-left: 18px; font-size: 13px;">
+                                                { for p.budget_cuts.iter().map(|c| html!{ <li>{c.describe()}</li> }) }
+                                            </ul>
+                                        }
+                                        if let Some(lost) = lost {
+                                            <p style="color: #b91c1c; margin: 4px 0;">
+                                                {format!("⚠ The Worker will cut {} from the front of this prompt — the {lost} will be lost.", length_label(&w.dropped))}
+                                            </p>
+                                        } else if w.truncated() {
+                                            <p style="color: #b45309; margin: 4px 0;">
+                                                {format!("The Worker will cut {} from the front of this prompt.", length_label(&w.dropped))}
+                                            </p>
+                                        }
+                                        <details>
+                                            <summary style="opacity:0.8;">
+                                                {format!("Final prompt sent by the Worker ({}; limit {} units)", length_label(&w.sent), MAX_WORKER_PROMPT_CHARS)}
+                                            </summary>
+                                            <pre style="white-space: pre-wrap; font-size: 12px; background: #f6f6f6; padding: 8px; border-radius: 6px;">
+                                                if w.truncated() {
+                                                    <del style="color: #b91c1c;">{w.dropped.clone()}</del>
+                                                }
+                                                {w.sent.clone()}
+                                            </pre>
+                                            if let Some(neg) = &w.negative {
+                                                <p style="font-size: 12px; opacity: 0.8;">{format!("Negative prompt: {neg}")}</p>
+                                            }
+                                        </details>
+                                    </>
+                                }
+                            }}
                         </div>
                     }
                 }) }
@@ -557,26 +1990,132 @@ fn app() -> Html {
             if *busy {
                 <p>{"Generating… (one request per image, then 16:9 PNG conversion)"}</p>
             }
+            if !slot_errors.is_empty() {
+                <details open=true style="border: 1px solid #fca5a5; border-radius: 10px; padding: 8px 10px; margin-bottom: 12px;">
+                    <summary style="color: #b91c1c; font-weight: 600;">{format!("Errors ({})", slot_errors.len())}</summary>
+                    <ul style="list-style: none; padding: 0; margin: 8px 0;">
+                        { for slot_errors.iter().map(|e| {
+                            let on_retry = {
+                                let key = e.key.clone();
+                                run_slots.reform(move |_: MouseEvent| Some(vec![key.clone()]))
+                            };
+                            let name = match e.variant {
+                                Some(v) => format!("{} #{}", pretty_slot_name(&e.key), v + 1),
+                                None => pretty_slot_name(&e.key),
+                            };
+                            html!{
+                                <li style="display:flex; gap: 8px; align-items: baseline; margin-bottom: 6px;">
+                                    <span style="opacity:0.7; font-family: monospace;">{e.time()}</span>
+                                    <b>{name}</b>
+                                    <span style="background: #fee2e2; border-radius: 4px; padding: 0 4px; font-size: 12px;">{e.error.label()}</span>
+                                    <span style="flex: 1;">
+                                        {e.error.to_string()}
+                                        if e.attempts > 1 {
+                                            <span style="opacity:0.7;">{format!(" (after {} attempts)", e.attempts)}</span>
+                                        }
+                                    </span>
+                                    <button onclick={on_retry} disabled={*busy || !settings_errors.is_empty()}>{"Retry"}</button>
+                                </li>
+                            }
+                        }) }
+                    </ul>
+                    <button onclick={on_clear_errors} disabled={*busy}>{"Clear"}</button>
+                </details>
+            }
 
             <div style="display: grid; grid-template-columns: repeat(2, 1fr); gap: 12px;">
                 { for (*images).iter().map(|img| {
                     let title = format!("{} • {}", pretty_slot_name(&img.key), img.preview_filename);
+                    let chosen = img.chosen().cloned().unwrap_or_default();
 
-                    let preview_href = img.preview_url.clone();
+                    let preview_href = chosen.preview_url.clone();
                     let preview_fn = img.preview_filename.clone();
 
-                    let dl_href = img.download_url.clone();
+                    let dl_href = chosen.download_url.clone();
                     let dl_fn = img.download_filename.clone();
 
+                    let choose = |i: usize| {
+                        let images = images.clone();
+                        let slot = img.slot;
+                        Callback::from(move |_| {
+                            let mut next = (*images).clone();
+                            if let Some(img) = next.iter_mut().find(|m| m.slot == slot) {
+                                img.chosen = i;
+                            }
+                            images.set(next);
+                        })
+                    };
+
+                    let on_regenerate = {
+                        let key = img.key.clone();
+                        run_slots.reform(move |_: MouseEvent| Some(vec![key.clone()]))
+                    };
+
+                    let on_lock_seed = {
+                        let project = project.clone();
+                        let slot = img.slot;
+                        let seed = chosen.seed;
+                        Callback::from(move |_| {
+                            update_project(&project, |p| {
+                                if let Some(item) = p.slots.iter_mut().find(|s| s.id == slot) {
+                                    item.seed = seed;
+                                    item.seed_locked = true;
+                                }
+                            });
+                        })
+                    };
+
                     html!{
                         <div style="border:1px solid #ddd; border-radius: 10px; padding: 10px;">
-                            <b>{title}</b>
-                            <img src={img.preview_url.clone()} style="width: 100%; border-radius: 8px; margin-top: 8px;" />
-
-                            <div style="display:flex; gap: 12px; margin-top: 10px; flex-wrap: wrap;">
-                                <a href={preview_href} download={preview_fn}>{"Download original (JPG)"}</a>
-                                <a style="font-weight: 600;" href={dl_href} download={dl_fn}>{"Download 16:9 (PNG)"} </a>
+                            <div style="display:flex; justify-content: space-between; gap: 10px;">
+                                <b>{title}</b>
+                                <button onclick={on_regenerate} disabled={*busy || !settings_errors.is_empty()}>{"Regenerate"}</button>
                             </div>
+                            if chosen.preview_url.is_empty() {
+                                <p style="opacity:0.7;">{"Generated in an earlier session — regenerate to preview and download."}</p>
+                            } else {
+                                <img src={chosen.preview_url.clone()} style="width: 100%; border-radius: 8px; margin-top: 8px;" />
+
+                                <div style="display:flex; gap: 12px; margin-top: 10px; flex-wrap: wrap;">
+                                    <a href={preview_href} download={preview_fn.clone()}>
+                                        {format!("Download original ({})", preview_fn.rsplit('.').next().unwrap_or_default().to_uppercase())}
+                                    </a>
+                                    <a style="font-weight: 600;" href={dl_href} download={dl_fn}>{"Download 16:9 (PNG)"} </a>
+                                </div>
+                            }
+                            if img.candidates.len() > 1 {
+                                <div style="display:flex; gap: 6px; margin-top: 8px; overflow-x: auto;">
+                                    { for img.candidates.iter().enumerate().map(|(i, c)| {
+                                        let border = if i == img.chosen { "2px solid #2563eb" } else { "2px solid transparent" };
+                                        html!{
+                                            <button
+                                                title={format!("Candidate {}{}", i + 1, c.seed.map(|s| format!(" • seed {s}")).unwrap_or_default())}
+                                                style={format!("border: {border}; border-radius: 6px; padding: 0; background: none; cursor: pointer;")}
+                                                onclick={choose(i)}
+                                                disabled={*busy}
+                                            >
+                                                if c.preview_url.is_empty() {
+                                                    <span style="display:inline-block; width: 96px; padding: 18px 0;">{format!("#{}", i + 1)}</span>
+                                                } else {
+                                                    <img src={c.preview_url.clone()} style="width: 96px; display: block; border-radius: 4px;" />
+                                                }
+                                            </button>
+                                        }
+                                    }) }
+                                </div>
+                                <p style="margin: 4px 0 0; font-size: 13px; opacity: 0.8;">
+                                    {format!("Candidate {} of {} chosen — downloads and exports use it.", img.chosen + 1, img.candidates.len())}
+                                </p>
+                            }
+                            if let Some(seed) = chosen.seed {
+                                <div style="display:flex; gap: 8px; align-items: center; margin-top: 8px; opacity: 0.85;">
+                                    <span>{format!("Seed {seed}")}</span>
+                                    <button onclick={on_lock_seed} disabled={*busy}>{"Lock this seed"}</button>
+                                </div>
+                            }
+                            if chosen.meta != ImageMeta::default() {
+                                <p style="margin: 6px 0 0; font-size: 13px; opacity: 0.8;">{chosen.meta.summary()}</p>
+                            }
                         </div>
                     }
                 }) }
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use crate::characters::Character;
+use crate::settings::GenSettings;
+use crate::slots::default_layout;
+use crate::{
+    load_local_storage, new_slot, refresh_slots, remove_local_storage, save_local_storage,
+    PromptItem, RenderedImage,
+};
+
+// ----------------------------
+// Project storage (one LocalStorage entry per book)
+// ----------------------------
+const LS_PROJECT_INDEX: &str = "ebook_prompt_studio_projects";
+const LS_ACTIVE_PROJECT: &str = "ebook_prompt_studio_active_project";
+const LS_PROJECT_PREFIX: &str = "ebook_prompt_studio_project_";
+
+// Single-book keys from before projects existed; migrated into a default project.
+const LS_LEGACY_PREMISE: &str = "ebook_prompt_studio_premise";
+const LS_LEGACY_SLOTS: &str = "ebook_prompt_studio_slots";
+const LS_LEGACY_CHARACTERS: &str = "ebook_prompt_studio_characters";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectMeta {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub premise: String,
+    #[serde(default)]
+    pub slots: Vec<PromptItem>,
+    #[serde(default)]
+    pub characters: Vec<Character>,
+    #[serde(default)]
+    pub settings: GenSettings,
+    #[serde(default)]
+    pub images: Vec<RenderedImage>, // metadata only; object URLs are per session
+}
+
+impl Project {
+    pub fn new(id: String, name: String) -> Self {
+        let mut p = Project {
+            id,
+            name,
+            premise: String::new(),
+            slots: default_layout().into_iter().map(new_slot).collect(),
+            characters: vec![],
+            settings: GenSettings::default(),
+            images: vec![],
+        };
+        refresh_slots(&mut p);
+        p
+    }
+
+    pub fn meta(&self) -> ProjectMeta {
+        ProjectMeta {
+            id: self.id.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+pub fn load_project_index() -> Vec<ProjectMeta> {
+    serde_json::from_str(&load_local_storage(LS_PROJECT_INDEX)).unwrap_or_default()
+}
+
+pub fn save_project_index(index: &[ProjectMeta]) {
+    if let Ok(json) = serde_json::to_string(index) {
+        save_local_storage(LS_PROJECT_INDEX, &json);
+    }
+}
+
+pub fn load_project(id: &str) -> Option<Project> {
+    let json = load_local_storage(&format!("{LS_PROJECT_PREFIX}{id}"));
+    let mut p = serde_json::from_str::<Project>(&json).ok()?;
+    if p.slots.is_empty() {
+        p.slots = default_layout().into_iter().map(new_slot).collect();
+    }
+    refresh_slots(&mut p);
+    Some(p)
+}
+
+pub fn save_project(p: &Project) {
+    if let Ok(json) = serde_json::to_string(p) {
+        save_local_storage(&format!("{LS_PROJECT_PREFIX}{}", p.id), &json);
+    }
+}
+
+pub fn delete_project(id: &str) {
+    remove_local_storage(&format!("{LS_PROJECT_PREFIX}{id}"));
+}
+
+pub fn save_active_project_id(id: &str) {
+    save_local_storage(LS_ACTIVE_PROJECT, id);
+}
+
+pub fn new_project_id(index: &[ProjectMeta]) -> String {
+    let mut n = js_sys::Date::now() as u64;
+    loop {
+        let id = format!("p{n:x}");
+        if !index.iter().any(|m| m.id == id) {
+            return id;
+        }
+        n += 1;
+    }
+}
+
+/// Add a freshly created (or duplicated) project to storage and the index.
+pub fn insert_project(index: &mut Vec<ProjectMeta>, p: &Project) {
+    save_project(p);
+    index.push(p.meta());
+    save_project_index(index);
+}
+
+/// Build the default project from the pre-project single-book keys.
+fn migrate_legacy(id: String) -> Project {
+    let mut p = Project::new(id, "My book".to_string());
+    p.premise = load_local_storage(LS_LEGACY_PREMISE);
+    if let Ok(slots) = serde_json::from_str::<Vec<PromptItem>>(&load_local_storage(LS_LEGACY_SLOTS))
+    {
+        if !slots.is_empty() {
+            p.slots = slots;
+        }
+    }
+    p.characters =
+        serde_json::from_str(&load_local_storage(LS_LEGACY_CHARACTERS)).unwrap_or_default();
+    refresh_slots(&mut p);
+
+    remove_local_storage(LS_LEGACY_PREMISE);
+    remove_local_storage(LS_LEGACY_SLOTS);
+    remove_local_storage(LS_LEGACY_CHARACTERS);
+    p
+}
+
+/// The project to open on startup: the last active one, else the first
+/// in the index, else a default project (migrated from legacy storage).
+pub fn load_startup_project() -> Project {
+    let mut index = load_project_index();
+
+    let active = load_local_storage(LS_ACTIVE_PROJECT);
+    let found = index
+        .iter()
+        .find(|m| m.id == active)
+        .or(index.first())
+        .and_then(|m| load_project(&m.id));
+
+    let p = match found {
+        Some(p) => p,
+        None => {
+            let p = migrate_legacy(new_project_id(&index));
+            index.retain(|m| load_project(&m.id).is_some());
+            insert_project(&mut index, &p);
+            p
+        }
+    };
+    save_active_project_id(&p.id);
+    p
+}
+
+// ----------------------------
+// Portable project file (export / import)
+// ----------------------------
+const PROJECT_FILE_FORMAT: &str = "ebook-prompt-studio/project";
+const PROJECT_FILE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ProjectFile {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    exported_at: String,
+    project: Project,
+}
+
+pub fn export_project_json(p: &Project) -> Result<String, String> {
+    let file = ProjectFile {
+        format: PROJECT_FILE_FORMAT.to_string(),
+        version: PROJECT_FILE_VERSION,
+        exported_at: String::from(js_sys::Date::new_0().to_iso_string()),
+        project: p.clone(),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| format!("Export failed: {e}"))
+}
+
+/// Parse an exported project file. The caller assigns a fresh id so an
+/// import never overwrites an existing project.
+pub fn parse_project_file(json: &str) -> Result<Project, String> {
+    let file: ProjectFile =
+        serde_json::from_str(json).map_err(|e| format!("Not a valid project file: {e}"))?;
+    if file.format != PROJECT_FILE_FORMAT {
+        return Err(format!("Unknown file format “{}”", file.format));
+    }
+    if file.version > PROJECT_FILE_VERSION {
+        return Err(format!(
+            "Project file version {} is newer than this app supports ({PROJECT_FILE_VERSION})",
+            file.version
+        ));
+    }
+
+    let mut p = file.project;
+    if p.slots.is_empty() {
+        p.slots = default_layout().into_iter().map(new_slot).collect();
+    }
+    refresh_slots(&mut p);
+    Ok(p)
+}
+
+/// Filesystem-friendly name for downloads ("My Book!" → "my-book").
+pub fn project_file_stem(name: &str) -> String {
+    let slug = name
+        .trim()
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "project".to_string()
+    } else {
+        slug
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::styles::DEFAULT_STYLE;
+
+// ----------------------------
+// Generation settings (mirror the Worker's accepted ranges)
+// ----------------------------
+pub const FLUX_STEPS_MAX: u32 = 8;
+pub const SDXL_SIZE_MIN: u32 = 256;
+pub const SDXL_SIZE_MAX: u32 = 2048;
+pub const SDXL_STEPS_MAX: u32 = 20;
+pub const SDXL_GUIDANCE_MIN: f32 = 1.0;
+pub const SDXL_GUIDANCE_MAX: f32 = 20.0;
+pub const CONCURRENCY_MAX: u32 = 6;
+pub const ATTEMPTS_MAX: u32 = 8;
+pub const VARIANTS_MAX: u32 = 4;
+pub const TIMEOUT_SECS_MIN: u32 = 5;
+pub const TIMEOUT_SECS_MAX: u32 = 600;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageModel {
+    #[default]
+    Flux,
+    Sdxl,
+}
+
+impl ImageModel {
+    pub const ALL: [ImageModel; 2] = [ImageModel::Flux, ImageModel::Sdxl];
+
+    /// Value of the Worker's `model` field.
+    pub fn id(self) -> &'static str {
+        match self {
+            ImageModel::Flux => "flux",
+            ImageModel::Sdxl => "sdxl",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ImageModel::Flux => "FLUX",
+            ImageModel::Sdxl => "SDXL",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<ImageModel> {
+        ImageModel::ALL.into_iter().find(|m| m.id() == id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenSettings {
+    pub model: ImageModel,
+    pub style: String,   // style preset id, see styles.rs
+    pub flux_steps: u32, // flux: 1–8
+
+    // sdxl
+    pub width: u32,
+    pub height: u32,
+    pub num_steps: u32,
+    pub guidance: f32,
+    pub negative_prompt: String, // added in front of the Worker's own no-text negatives
+
+    // prompt building
+    pub compact_for_worker: bool, // leave out lines the Worker appends itself
+
+    // batch
+    pub concurrency: u32,  // requests in flight at once: 1–6
+    pub max_attempts: u32, // tries per image, including the first: 1–8
+    pub variants: u32,     // candidates per slot, seeds incremented: 1–4
+    pub timeout_secs: u32, // per HTTP call, queue waits excluded; a hung request is aborted and retried: 5–600
+}
+
+impl Default for GenSettings {
+    fn default() -> Self {
+        GenSettings {
+            model: ImageModel::Flux,
+            style: DEFAULT_STYLE.to_string(),
+            flux_steps: FLUX_STEPS_MAX,
+            width: 1344,
+            height: 768,
+            num_steps: SDXL_STEPS_MAX,
+            guidance: 7.5,
+            negative_prompt: String::new(),
+            compact_for_worker: false,
+            concurrency: 3,
+            max_attempts: 3,
+            variants: 1,
+            timeout_secs: 120,
+        }
+    }
+}
+
+impl GenSettings {
+    /// Problems that would make the Worker clamp (i.e. silently change) a value,
+    /// plus batch options out of range.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errs = vec![];
+        if !(1..=CONCURRENCY_MAX).contains(&self.concurrency) {
+            errs.push(format!("Parallel requests must be 1–{CONCURRENCY_MAX}."));
+        }
+        if !(1..=ATTEMPTS_MAX).contains(&self.max_attempts) {
+            errs.push(format!("Attempts per image must be 1–{ATTEMPTS_MAX}."));
+        }
+        if !(1..=VARIANTS_MAX).contains(&self.variants) {
+            errs.push(format!("Candidates per slot must be 1–{VARIANTS_MAX}."));
+        }
+        if !(TIMEOUT_SECS_MIN..=TIMEOUT_SECS_MAX).contains(&self.timeout_secs) {
+            errs.push(format!(
+                "Request timeout must be {TIMEOUT_SECS_MIN}–{TIMEOUT_SECS_MAX} s."
+            ));
+        }
+        match self.model {
+            ImageModel::Flux => {
+                if !(1..=FLUX_STEPS_MAX).contains(&self.flux_steps) {
+                    errs.push(format!("FLUX steps must be 1–{FLUX_STEPS_MAX}."));
+                }
+            }
+            ImageModel::Sdxl => {
+                for (name, v) in [("Width", self.width), ("Height", self.height)] {
+                    if !(SDXL_SIZE_MIN..=SDXL_SIZE_MAX).contains(&v) {
+                        errs.push(format!(
+                            "{name} must be {SDXL_SIZE_MIN}–{SDXL_SIZE_MAX} px."
+                        ));
+                    }
+                }
+                if !(1..=SDXL_STEPS_MAX).contains(&self.num_steps) {
+                    errs.push(format!("SDXL steps must be 1–{SDXL_STEPS_MAX}."));
+                }
+                if !(SDXL_GUIDANCE_MIN..=SDXL_GUIDANCE_MAX).contains(&self.guidance) {
+                    errs.push(format!(
+                        "Guidance must be {SDXL_GUIDANCE_MIN}–{SDXL_GUIDANCE_MAX}."
+                    ));
+                }
+            }
+        }
+        errs
+    }
+}
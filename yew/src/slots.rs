@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+// ----------------------------
+// Slot layout (front matter → chapters → back matter)
+// ----------------------------
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotKind {
+    Cover,
+    FrontMatter,
+    Prologue,
+    Chapter,
+    Interlude,
+    Epilogue,
+    BackMatter,
+    Credits,
+}
+
+impl SlotKind {
+    pub const ALL: [SlotKind; 8] = [
+        SlotKind::Cover,
+        SlotKind::FrontMatter,
+        SlotKind::Prologue,
+        SlotKind::Chapter,
+        SlotKind::Interlude,
+        SlotKind::Epilogue,
+        SlotKind::BackMatter,
+        SlotKind::Credits,
+    ];
+
+    /// Key prefix used for slot keys and filenames ("ch" → "ch12.jpg").
+    pub fn prefix(self) -> &'static str {
+        match self {
+            SlotKind::Cover => "cover",
+            SlotKind::FrontMatter => "front",
+            SlotKind::Prologue => "prologue",
+            SlotKind::Chapter => "ch",
+            SlotKind::Interlude => "interlude",
+            SlotKind::Epilogue => "epilogue",
+            SlotKind::BackMatter => "back",
+            SlotKind::Credits => "credits",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SlotKind::Cover => "Cover",
+            SlotKind::FrontMatter => "Front matter",
+            SlotKind::Prologue => "Prologue",
+            SlotKind::Chapter => "Chapter",
+            SlotKind::Interlude => "Interlude",
+            SlotKind::Epilogue => "Epilogue",
+            SlotKind::BackMatter => "Back matter",
+            SlotKind::Credits => "Credits",
+        }
+    }
+
+    /// Numbered kinds can repeat; the others appear at most once per book.
+    pub fn numbered(self) -> bool {
+        matches!(
+            self,
+            SlotKind::FrontMatter | SlotKind::Chapter | SlotKind::Interlude | SlotKind::BackMatter
+        )
+    }
+
+    /// Book section, used to decide where a newly added slot goes.
+    fn section(self) -> u8 {
+        match self {
+            SlotKind::Cover => 0,
+            SlotKind::FrontMatter => 1,
+            SlotKind::Prologue => 2,
+            SlotKind::Chapter | SlotKind::Interlude => 3,
+            SlotKind::Epilogue => 4,
+            SlotKind::BackMatter => 5,
+            SlotKind::Credits => 6,
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<SlotKind> {
+        SlotKind::ALL.into_iter().find(|k| k.prefix() == prefix)
+    }
+}
+
+/// The classic ten-slot book: Cover → Prologue → 6 chapters → Epilogue → Credits.
+pub fn default_layout() -> Vec<SlotKind> {
+    let mut out = vec![SlotKind::Cover, SlotKind::Prologue];
+    out.extend(std::iter::repeat_n(SlotKind::Chapter, 6));
+    out.extend([SlotKind::Epilogue, SlotKind::Credits]);
+    out
+}
+
+/// Derive unique slot keys for a layout, in order ("cover", "ch1", "interlude2"...).
+pub fn slot_keys(layout: &[SlotKind]) -> Vec<String> {
+    let mut counts = [0usize; SlotKind::ALL.len()];
+    layout
+        .iter()
+        .map(|k| {
+            let i = SlotKind::ALL.iter().position(|a| a == k).unwrap_or(0);
+            counts[i] += 1;
+            if k.numbered() {
+                format!("{}{}", k.prefix(), counts[i])
+            } else {
+                k.prefix().to_string()
+            }
+        })
+        .collect()
+}
+
+/// Split a slot key into its kind and 1-based number ("ch12" → Chapter, 12).
+/// Singleton kinds report number 1.
+pub fn parse_slot_key(key: &str) -> Option<(SlotKind, usize)> {
+    let digits_at = key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len());
+    let (prefix, digits) = key.split_at(digits_at);
+    let kind = SlotKind::from_prefix(prefix)?;
+    match (kind.numbered(), digits.is_empty()) {
+        (true, false) => digits.parse().ok().map(|n| (kind, n)),
+        (false, true) => Some((kind, 1)),
+        _ => None,
+    }
+}
+
+pub fn pretty_slot_name(key: &str) -> String {
+    match parse_slot_key(key) {
+        Some((kind, n)) if kind.numbered() => format!("{} {n}", kind.label()),
+        Some((kind, _)) => kind.label().to_string(),
+        None => key.to_string(),
+    }
+}
+
+pub fn can_add_slot(layout: &[SlotKind], kind: SlotKind) -> bool {
+    kind.numbered() || !layout.contains(&kind)
+}
+
+/// Index at which a new slot of `kind` is inserted: after the last slot
+/// in the same or an earlier book section.
+pub fn insert_position(layout: &[SlotKind], kind: SlotKind) -> usize {
+    layout
+        .iter()
+        .rposition(|k| k.section() <= kind.section())
+        .map(|i| i + 1)
+        .unwrap_or(0)
+}
@@ -0,0 +1,85 @@
+// ----------------------------
+// Style presets
+// ----------------------------
+// Each preset carries the client-side prompt fragment plus the `style` value
+// sent to the Worker. The Worker only knows "animated3d" and "storybook", so
+// painterly looks map onto "storybook".
+
+pub struct StylePreset {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub fragment: &'static str,
+    pub worker_style: &'static str,
+}
+
+pub const DEFAULT_STYLE: &str = "animated3d";
+
+pub const STYLE_PRESETS: &[StylePreset] = &[
+    StylePreset {
+        id: "animated3d",
+        label: "3D animated film",
+        // Safe “Pixar-adjacent” vibe without naming a specific studio.
+        fragment: "High-quality 3D animated family film look, soft global illumination, warm cinematic lighting, \
+                   detailed materials, subtle subsurface scattering, clean shapes, crisp focus on subject, \
+                   gentle depth of field, ultra clean render.",
+        worker_style: "animated3d",
+    },
+    StylePreset {
+        id: "storybook",
+        label: "Storybook",
+        fragment: "Classic storybook illustration, soft painterly textures, warm cinematic lighting, \
+                   gentle colors, clean composition, soft depth of field.",
+        worker_style: "storybook",
+    },
+    StylePreset {
+        id: "watercolor",
+        label: "Watercolor",
+        fragment: "Soft watercolor illustration, translucent washes, visible paper grain, \
+                   gentle bleeding edges, airy pastel palette, loose but clear shapes.",
+        worker_style: "storybook",
+    },
+    StylePreset {
+        id: "paper_cutout",
+        label: "Paper cutout",
+        fragment: "Layered paper-cutout illustration, handcrafted cut paper shapes, subtle drop shadows between layers, \
+                   tactile card textures, bold simple silhouettes.",
+        worker_style: "storybook",
+    },
+    StylePreset {
+        id: "gouache",
+        label: "Gouache",
+        fragment: "Gouache painting, opaque matte brushwork, rich flat color areas, visible brush texture, \
+                   mid-century children's illustration feel.",
+        worker_style: "storybook",
+    },
+    StylePreset {
+        id: "flat_vector",
+        label: "Flat vector",
+        fragment: "Flat vector illustration, clean geometric shapes, limited harmonious palette, \
+                   minimal shading, crisp edges, uncluttered background.",
+        worker_style: "storybook",
+    },
+    StylePreset {
+        id: "colored_pencil",
+        label: "Colored pencil",
+        fragment: "Colored pencil illustration, visible hatching strokes, warm paper tone, \
+                   soft layered colors, hand-drawn charm.",
+        worker_style: "storybook",
+    },
+    StylePreset {
+        id: "claymation",
+        label: "Clay stop-motion",
+        fragment: "Handmade clay stop-motion look, soft sculpted shapes with subtle fingerprints, \
+                   miniature set with practical lighting, charming imperfect details.",
+        worker_style: "animated3d",
+    },
+];
+
+/// Look up a preset by id, falling back to the default for unknown ids.
+pub fn style_preset(id: &str) -> &'static StylePreset {
+    STYLE_PRESETS
+        .iter()
+        .find(|p| p.id == id)
+        .or_else(|| STYLE_PRESETS.iter().find(|p| p.id == DEFAULT_STYLE))
+        .unwrap_or(&STYLE_PRESETS[0])
+}
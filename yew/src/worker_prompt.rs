@@ -0,0 +1,247 @@
+use crate::settings::ImageModel;
+use crate::styles::StylePreset;
+use crate::MAX_WORKER_PROMPT_CHARS;
+
+// ----------------------------
+// Worker prompt simulation
+// ----------------------------
+// Mirrors buildStyledPrompt() in worker/src/index.ts so the UI can show the
+// exact prompt the model receives. Keep these strings in sync with the Worker.
+
+const WORKER_ANTI_TEXT: &str = "ABSOLUTELY NO TEXT: no letters, no words, no numbers, no symbols, no signage, no labels, no captions, \
+no book covers with titles, no misspellings, no gibberish. \
+If any sign, poster, menu, label, packaging, screen, or book spine appears, it must be BLANK and UNREADABLE. \
+No logos, no watermark, no signature.";
+
+const WORKER_AVOID_TEXT_PROPS: &str = "Avoid text-bearing elements: posters, banners, street signs, storefront signs, menus, UI screens, labels, packaging, \
+newspapers, magazines, chalkboards, whiteboards, license plates, book spines with titles. Prefer plain surfaces and simple shapes.";
+
+const WORKER_CROP_SAFE: &str =
+    "Keep key subjects centered with generous margins; avoid important details near edges (crop-safe 16:9).";
+
+const WORKER_BASE_SAFETY: &str =
+    "Family-friendly. Natural proportions. No gore. No violence. No weapons. No horror imagery.";
+
+const WORKER_STORYBOOK: &str =
+    "Storybook illustration, warm cinematic lighting, clean composition, soft depth of field, high detail.";
+
+const WORKER_ANIMATED_3D: &str = "High-quality 3D animated family film look, soft global illumination, warm rim light, detailed materials, subtle subsurface scattering, \
+clean shapes, cinematic depth of field, sharp focus on subject, ultra clean render.";
+
+// SDXL only: sent as negative_prompt, appended after any user negative prompt.
+const WORKER_SDXL_NEGATIVE: &str =
+    "text, letters, words, typography, caption, subtitle, title, logo, watermark, signature, \
+signage, street sign, label, menu, poster, banner, book cover text, misspelling, gibberish, \
+license plate, UI, screen text, packaging text, \
+low quality, blurry, grain, noise, deformed, malformed hands, extra fingers";
+
+fn worker_style_line(worker_style: &str) -> &'static str {
+    if worker_style == "animated3d" {
+        WORKER_ANIMATED_3D
+    } else {
+        WORKER_STORYBOOK
+    }
+}
+
+fn worker_suffix(worker_style: &str) -> String {
+    format!(
+        "\n\n{WORKER_CROP_SAFE}\n{}\n{WORKER_ANTI_TEXT}\n{WORKER_AVOID_TEXT_PROPS}\n{WORKER_BASE_SAFETY}",
+        worker_style_line(worker_style)
+    )
+}
+
+// ----------------------------
+// Length model (JavaScript `.length`)
+// ----------------------------
+// The Worker measures prompts with `.length`, which counts UTF-16 code units:
+// emoji and other non-BMP characters count as 2. All budgets use this unit;
+// cuts only ever land on char boundaries so no code point is split.
+
+/// Length as the Worker sees it (UTF-16 code units).
+pub fn js_length(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+/// "2048 units / 2031 chars" — both counts, for the UI.
+pub fn length_label(s: &str) -> String {
+    format!(
+        "{} UTF-16 units / {} chars",
+        js_length(s),
+        s.chars().count()
+    )
+}
+
+/// Byte index of the last char boundary within the first `units` code units.
+pub fn floor_utf16_boundary(s: &str, units: usize) -> usize {
+    let mut seen = 0;
+    for (i, c) in s.char_indices() {
+        seen += c.len_utf16();
+        if seen > units {
+            return i;
+        }
+    }
+    s.len()
+}
+
+/// Byte index of the first char boundary at or after `units` code units.
+/// A cut inside a surrogate pair moves past the whole character.
+pub fn ceil_utf16_boundary(s: &str, units: usize) -> usize {
+    let mut seen = 0;
+    for (i, c) in s.char_indices() {
+        if seen >= units {
+            return i;
+        }
+        seen += c.len_utf16();
+    }
+    s.len()
+}
+
+/// UTF-16 units the Worker appends after our prompt for this style.
+pub fn worker_overhead(worker_style: &str) -> usize {
+    js_length(&worker_suffix(worker_style))
+}
+
+/// UTF-16 units left for our own prompt before the Worker starts cutting from the front.
+pub fn client_prompt_budget(worker_style: &str) -> usize {
+    MAX_WORKER_PROMPT_CHARS.saturating_sub(worker_overhead(worker_style))
+}
+
+/// Client instructions the Worker already appends for a style. Compact mode
+/// leaves them out of our prompt. FLUX and SDXL get the same suffix (SDXL
+/// additionally gets the no-text negatives), so only the style matters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorkerCoverage {
+    pub no_text: bool,   // WORKER_ANTI_TEXT / WORKER_AVOID_TEXT_PROPS
+    pub safety: bool,    // WORKER_BASE_SAFETY
+    pub crop_safe: bool, // WORKER_CROP_SAFE
+    pub style: bool,     // the preset's look is the Worker's own style line
+}
+
+pub fn worker_coverage(preset: &StylePreset) -> WorkerCoverage {
+    WorkerCoverage {
+        no_text: true,
+        safety: true,
+        crop_safe: true,
+        // Painterly presets only map onto "storybook"; their fragment still matters.
+        style: preset.id == preset.worker_style,
+    }
+}
+
+/// What the Worker will actually send to the model for one request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkerPrompt {
+    pub sent: String,             // final prompt after the Worker's trim
+    pub dropped: String,          // text the Worker cuts from the front (empty when it fits)
+    pub negative: Option<String>, // SDXL negative_prompt
+}
+
+impl WorkerPrompt {
+    pub fn truncated(&self) -> bool {
+        !self.dropped.is_empty()
+    }
+}
+
+/// Reproduce the Worker's prompt assembly for `model` / worker `style`.
+/// FLUX and SDXL receive the same prompt; SDXL also gets a negative prompt.
+pub fn simulate_worker_prompt(
+    user_prompt: &str,
+    worker_style: &str,
+    model: ImageModel,
+    user_negative: &str,
+) -> WorkerPrompt {
+    let full = format!("{}{}", user_prompt.trim(), worker_suffix(worker_style));
+    let full = full.trim();
+
+    // trimToMaxPrompt(): keep the *end* of the prompt, skipping a dangling low surrogate
+    let cut = js_length(full).saturating_sub(MAX_WORKER_PROMPT_CHARS);
+    let split = ceil_utf16_boundary(full, cut);
+
+    let negative = (model == ImageModel::Sdxl).then(|| {
+        let user_negative = user_negative.trim();
+        if user_negative.is_empty() {
+            WORKER_SDXL_NEGATIVE.to_string()
+        } else {
+            format!("{user_negative}, {WORKER_SDXL_NEGATIVE}")
+        }
+    });
+
+    WorkerPrompt {
+        sent: full[split..].to_string(),
+        dropped: full[..split].to_string(),
+        negative,
+    }
+}
+
+/// Which parts of a slot's own text the Worker's trim would lose.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruncationCheck {
+    pub worker: WorkerPrompt,
+    pub premise_cut: bool,
+    pub scene_cut: bool,
+}
+
+/// Simulate the Worker for one slot and flag whether the premise or the
+/// scene text (as found in the prompt) falls inside the dropped prefix.
+pub fn check_truncation(
+    user_prompt: &str,
+    premise: &str,
+    scene: &str,
+    worker_style: &str,
+    model: ImageModel,
+    user_negative: &str,
+) -> TruncationCheck {
+    let worker = simulate_worker_prompt(user_prompt, worker_style, model, user_negative);
+    let dropped = js_length(&worker.dropped);
+    let user = user_prompt.trim();
+
+    // A part is cut when it starts inside the dropped prefix.
+    let starts_in_cut = |part: &str| {
+        let part = part.trim();
+        !part.is_empty()
+            && user
+                .find(part)
+                .map(|i| js_length(&user[..i]) < dropped)
+                .unwrap_or(false)
+    };
+
+    TruncationCheck {
+        premise_cut: starts_in_cut(premise),
+        scene_cut: starts_in_cut(scene),
+        worker,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundaries_never_split_a_surrogate_pair() {
+        let s = "a😀b"; // units: a=1, 😀=2, b=1
+        assert_eq!(floor_utf16_boundary(s, 2), 1);
+        assert_eq!(ceil_utf16_boundary(s, 2), 1 + '😀'.len_utf8());
+        assert_eq!(floor_utf16_boundary(s, 3), 1 + '😀'.len_utf8());
+        assert_eq!(ceil_utf16_boundary(s, 1), 1);
+    }
+
+    #[test]
+    fn worker_trim_skips_emoji_at_the_cut() {
+        let style = "storybook";
+        // Make the Worker's cut land between the emoji's two UTF-16 units.
+        let tail = "b".repeat(MAX_WORKER_PROMPT_CHARS - 1 - worker_overhead(style));
+        let prompt = format!("{}😀{tail}", "a".repeat(10));
+        let w = simulate_worker_prompt(&prompt, style, ImageModel::Flux, "");
+
+        assert!(w.dropped.ends_with('😀'));
+        assert!(w.sent.starts_with('b'));
+        assert_eq!(js_length(&w.sent), MAX_WORKER_PROMPT_CHARS - 1);
+    }
+
+    #[test]
+    fn worker_trim_keeps_short_prompts() {
+        let w = simulate_worker_prompt("a small fox 🦊", "storybook", ImageModel::Sdxl, "");
+        assert!(!w.truncated());
+        assert!(w.sent.starts_with("a small fox 🦊"));
+        assert!(w.negative.is_some());
+    }
+}